@@ -0,0 +1,79 @@
+//! Renders a frame of 64 small spheres, the sort of scene `Bounce` and
+//! `Waves` produce, to compare evaluating every sphere at every voxel with
+//! the single pass in `render_sdf`, and with `MultiUnion` skipping spheres
+//! whose bounding boxes are too far away to matter. The spheres fill the
+//! cube, so no whole voxels are culled.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use cube_core::frame::Frame;
+use cube_thing::sdf::{render_sdf, Bounded, MultiUnion};
+use nalgebra::{vector, Vector3};
+use palette::LinSrgba;
+use sdfu::SDF;
+
+const SPHERES: usize = 64;
+
+fn positions() -> impl Iterator<Item = Vector3<f32>> {
+    // a fixed scatter through the cube, so runs are comparable
+    (0..SPHERES).map(|i| {
+        let i = i as f32;
+
+        vector![
+            (i * 2.39).sin() * 3.5 + 3.5,
+            (i * 0.37) % 7.0,
+            (i * 1.73).cos() * 3.5 + 3.5
+        ]
+    })
+}
+
+fn colour(i: usize) -> LinSrgba {
+    LinSrgba::new((i % 4) as f32 / 3.0, 0.5, 1.0 - (i % 3) as f32 / 2.0, 1.0)
+}
+
+/// How `render_sdf` used to work, a `dist` and then a `colour` call per voxel
+/// which each walk every sphere.
+fn render_naive<S: SDF<f32, Vector3<f32>>>(sdf: S, frame: &mut Frame) {
+    for (x, y, z, pix) in frame.pixels_mut() {
+        let pos = vector![x as f32, y as f32, z as f32];
+        let dist = sdf.dist(pos);
+        let mut colour = sdf.colour(pos);
+
+        if dist > 0.2 {
+            colour.alpha *= 1.0 / dist.powi(5);
+        }
+
+        *pix = colour;
+    }
+}
+
+fn bench_spheres(c: &mut Criterion) {
+    let plain = positions()
+        .enumerate()
+        .map(|(i, p)| sdfu::Sphere::new(0.5, colour(i)).translate(p))
+        .collect::<Vec<_>>();
+
+    let bounded = positions()
+        .enumerate()
+        .map(|(i, p)| Bounded::sphere(p, 0.5, colour(i)))
+        .collect::<Vec<_>>();
+
+    let mut frame = Frame::new();
+    let mut group = c.benchmark_group("64 spheres");
+
+    group.bench_function("naive", |b| {
+        b.iter(|| render_naive(MultiUnion::smooth(black_box(&plain), 0.8), &mut frame))
+    });
+
+    group.bench_function("single pass", |b| {
+        b.iter(|| render_sdf(MultiUnion::smooth(black_box(&plain), 0.8), &mut frame))
+    });
+
+    group.bench_function("single pass, culled", |b| {
+        b.iter(|| render_sdf(MultiUnion::smooth(black_box(&bounded), 0.8), &mut frame))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_spheres);
+criterion_main!(benches);
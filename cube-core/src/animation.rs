@@ -1,13 +1,20 @@
+use core::time::Duration;
+#[cfg(feature = "std")]
 use std::sync::{Arc, RwLock};
-use std::time::{Duration, Instant};
 
-use crate::frame::Frame;
+use crate::{frame::Frame, time};
 
 pub trait Animation {
     fn next_frame(&mut self, frame: &mut Frame);
 
     fn reset(&mut self);
 
+    /// Whether the animation has finished, animations which run forever can
+    /// leave this as is.
+    fn ended(&self) -> bool {
+        false
+    }
+
     fn with_fps(self, fps: f32) -> FixedFPSAnimation<Self>
     where
         Self: Sized,
@@ -15,7 +22,7 @@ pub trait Animation {
         FixedFPSAnimation {
             inner: self,
             interval: Duration::from_secs_f32(1.0 / fps),
-            last_frame: Instant::now(),
+            last_frame: time::now(),
         }
     }
 
@@ -26,7 +33,7 @@ pub trait Animation {
         TimeLimitedAnimation {
             inner: self,
             duration,
-            started: Instant::now(),
+            started: time::now(),
         }
     }
 
@@ -53,35 +60,9 @@ pub trait Animation {
     }
 }
 
-pub trait TerminatingAnimation: Animation {
-    fn ended(&self) -> bool;
-}
-
-pub trait MaybeTerminatingAnimation: Animation {
-    fn maybe_ended(&self) -> bool;
-}
-
-impl<T: Animation> MaybeTerminatingAnimation for T {
-    default fn maybe_ended(&self) -> bool {
-        false
-    }
-}
-
-macro_rules! impl_maybe_term {
-    ( $( $name:ident $(< $( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+ >)? ),+ ) => {
-        $(
-            impl $(< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)?
-                MaybeTerminatingAnimation
-            for $name
-                $(< $( $lt ),+ >)?
-            {
-                fn maybe_ended(&self) -> bool {
-                    self.ended()
-                }
-            }
-        )+
-    }
-}
+/// Marks animations whose `ended` eventually returns true, which is what
+/// `chain` and `repeat` need to move on.
+pub trait TerminatingAnimation: Animation {}
 
 #[derive(Debug)]
 #[cfg_attr(feature = "visual", derive(bevy_inspector_egui::Inspectable))]
@@ -90,13 +71,13 @@ pub struct FixedFPSAnimation<T> {
     #[cfg_attr(feature = "visual", inspectable(ignore))]
     interval: Duration,
     #[cfg_attr(feature = "visual", inspectable(ignore))]
-    last_frame: Instant,
+    last_frame: Duration,
 }
 
 impl<T: Animation> Animation for FixedFPSAnimation<T> {
     fn next_frame(&mut self, frame: &mut Frame) {
-        let now = Instant::now();
-        if now.duration_since(self.last_frame) > self.interval {
+        let now = time::now();
+        if now.saturating_sub(self.last_frame) > self.interval {
             self.inner.next_frame(frame);
             self.last_frame = now;
         }
@@ -105,15 +86,13 @@ impl<T: Animation> Animation for FixedFPSAnimation<T> {
     fn reset(&mut self) {
         self.inner.reset();
     }
-}
 
-impl<T: TerminatingAnimation> TerminatingAnimation for FixedFPSAnimation<T> {
     fn ended(&self) -> bool {
         self.inner.ended()
     }
 }
 
-impl_maybe_term!(FixedFPSAnimation<T: TerminatingAnimation>);
+impl<T: TerminatingAnimation> TerminatingAnimation for FixedFPSAnimation<T> {}
 
 #[derive(Debug)]
 #[cfg_attr(feature = "visual", derive(bevy_inspector_egui::Inspectable))]
@@ -122,7 +101,7 @@ pub struct TimeLimitedAnimation<T> {
     #[cfg_attr(feature = "visual", inspectable(ignore))]
     duration: Duration,
     #[cfg_attr(feature = "visual", inspectable(ignore))]
-    started: Instant,
+    started: Duration,
 }
 
 impl<T: Animation> Animation for TimeLimitedAnimation<T> {
@@ -132,21 +111,21 @@ impl<T: Animation> Animation for TimeLimitedAnimation<T> {
 
     fn reset(&mut self) {
         self.inner.reset();
-        self.started = Instant::now();
+        self.started = time::now();
     }
-}
 
-impl<T: MaybeTerminatingAnimation> TerminatingAnimation for TimeLimitedAnimation<T> {
     fn ended(&self) -> bool {
-        if Instant::now().duration_since(self.started) > self.duration {
-            dbg!("resetting time animation", std::any::type_name::<T>());
+        if time::now().saturating_sub(self.started) > self.duration {
+            log!("resetting time animation", core::any::type_name::<T>());
             return true;
         }
 
-        self.inner.maybe_ended()
+        self.inner.ended()
     }
 }
 
+impl<T: Animation> TerminatingAnimation for TimeLimitedAnimation<T> {}
+
 #[derive(Debug)]
 #[cfg_attr(feature = "visual", derive(bevy_inspector_egui::Inspectable))]
 pub struct ChainedAnimation<T, U> {
@@ -159,10 +138,10 @@ impl<T: TerminatingAnimation, U: Animation> Animation for ChainedAnimation<T, U>
     fn next_frame(&mut self, frame: &mut Frame) {
         if !self.current {
             if self.a.ended() {
-                dbg!(
+                log!(
                     "switching chain animation",
-                    std::any::type_name::<T>(),
-                    std::any::type_name::<U>()
+                    core::any::type_name::<T>(),
+                    core::any::type_name::<U>()
                 );
                 self.current = !self.current;
                 self.b.reset();
@@ -180,19 +159,18 @@ impl<T: TerminatingAnimation, U: Animation> Animation for ChainedAnimation<T, U>
         self.a.reset();
         self.current = false;
     }
+
+    fn ended(&self) -> bool {
+        self.current && self.b.ended()
+    }
 }
 
 // chained animations only terminate if the final animation terminates
 impl<T: TerminatingAnimation, U: TerminatingAnimation> TerminatingAnimation
     for ChainedAnimation<T, U>
 {
-    fn ended(&self) -> bool {
-        self.current && self.b.ended()
-    }
 }
 
-impl_maybe_term!(ChainedAnimation<T: TerminatingAnimation, U: TerminatingAnimation>);
-
 #[derive(Debug)]
 #[cfg_attr(feature = "visual", derive(bevy_inspector_egui::Inspectable))]
 pub struct RepeatedAnimation<T> {
@@ -206,9 +184,9 @@ impl<T: TerminatingAnimation> Animation for RepeatedAnimation<T> {
         if self.inner.ended() && self.count < self.loops {
             self.inner.reset();
             self.count += 1;
-            dbg!(
+            log!(
                 "repeating repeat animation",
-                std::any::type_name::<T>(),
+                core::any::type_name::<T>(),
                 self.count
             );
         }
@@ -220,16 +198,15 @@ impl<T: TerminatingAnimation> Animation for RepeatedAnimation<T> {
         self.inner.reset();
         self.count = 0;
     }
-}
 
-impl<T: TerminatingAnimation> TerminatingAnimation for RepeatedAnimation<T> {
     fn ended(&self) -> bool {
         self.count >= self.loops && self.inner.ended()
     }
 }
 
-impl_maybe_term!(RepeatedAnimation<T: TerminatingAnimation>);
+impl<T: TerminatingAnimation> TerminatingAnimation for RepeatedAnimation<T> {}
 
+#[cfg(feature = "std")]
 impl<T: Animation> Animation for RwLock<T> {
     fn next_frame(&mut self, frame: &mut Frame) {
         self.write().unwrap().next_frame(frame);
@@ -238,14 +215,16 @@ impl<T: Animation> Animation for RwLock<T> {
     fn reset(&mut self) {
         self.write().unwrap().reset();
     }
-}
 
-impl<T: TerminatingAnimation> TerminatingAnimation for RwLock<T> {
     fn ended(&self) -> bool {
         self.read().unwrap().ended()
     }
 }
 
+#[cfg(feature = "std")]
+impl<T: TerminatingAnimation> TerminatingAnimation for RwLock<T> {}
+
+#[cfg(feature = "std")]
 impl<T: Animation> Animation for Arc<RwLock<T>> {
     fn next_frame(&mut self, frame: &mut Frame) {
         self.write().unwrap().next_frame(frame);
@@ -254,10 +233,11 @@ impl<T: Animation> Animation for Arc<RwLock<T>> {
     fn reset(&mut self) {
         self.write().unwrap().reset();
     }
-}
 
-impl<T: TerminatingAnimation> TerminatingAnimation for Arc<RwLock<T>> {
     fn ended(&self) -> bool {
         self.read().unwrap().ended()
     }
 }
+
+#[cfg(feature = "std")]
+impl<T: TerminatingAnimation> TerminatingAnimation for Arc<RwLock<T>> {}
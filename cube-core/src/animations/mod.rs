@@ -0,0 +1,2 @@
+pub mod noise_field;
+pub mod sine_thing;
@@ -0,0 +1,207 @@
+use palette::LinSrgba;
+
+use crate::{
+    animation::Animation,
+    colour_map::ColourMap,
+    frame::Frame,
+    math,
+    noise::{Fbm, Field, Simplex, Warp, Worley},
+};
+
+/// What the noise is shaped into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pattern {
+    /// Fractal noise filling the cube, its colours slowly cycling.
+    Plasma,
+    /// Swirling blobs drifting upwards.
+    LavaLamp,
+    /// Wisps of fractal noise blowing sideways.
+    Clouds,
+    /// The walls between wandering Worley cells.
+    Cells,
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Animated noise sampled at each voxel and coloured with a `ColourMap`.
+#[cfg_attr(feature = "visual", derive(bevy_inspector_egui::Inspectable))]
+pub struct NoiseField {
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    pattern: Pattern,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    colours: ColourMap,
+    /// Noise units per voxel, smaller zooms in.
+    #[cfg_attr(feature = "visual", inspectable(min = 0.01, max = 1.0))]
+    scale: f32,
+    /// Noise time per frame.
+    #[cfg_attr(feature = "visual", inspectable(min = 0.0, max = 0.2))]
+    speed: f32,
+    #[cfg_attr(feature = "visual", inspectable(min = 1, max = 6))]
+    octaves: u32,
+    /// How far the domain is warped, lava lamps and cells only.
+    #[cfg_attr(feature = "visual", inspectable(min = 0.0, max = 4.0))]
+    warp: f32,
+    /// Where the pattern starts to show, lava lamps and clouds only.
+    #[cfg_attr(feature = "visual", inspectable(min = -1.0, max = 1.0))]
+    threshold: f32,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    simplex: Simplex,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    worley: Worley,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    time: f32,
+}
+
+impl NoiseField {
+    pub fn new(pattern: Pattern, colours: ColourMap) -> Self {
+        Self {
+            pattern,
+            colours,
+            scale: 0.2,
+            speed: 0.02,
+            octaves: 3,
+            warp: 0.0,
+            threshold: 0.0,
+            simplex: Simplex::default(),
+            worley: Worley::default(),
+            time: 0.0,
+        }
+    }
+
+    pub fn plasma() -> Self {
+        Self::new(Pattern::Plasma, ColourMap::rainbow()).with_octaves(2)
+    }
+
+    pub fn lava_lamp() -> Self {
+        Self::new(Pattern::LavaLamp, ColourMap::lava())
+            .with_scale(0.18)
+            .with_speed(0.015)
+            .with_warp(1.2)
+            .with_threshold(0.1)
+    }
+
+    pub fn clouds() -> Self {
+        Self::new(Pattern::Clouds, ColourMap::clouds())
+            .with_scale(0.15)
+            .with_speed(0.01)
+            .with_octaves(4)
+            .with_threshold(-0.1)
+    }
+
+    pub fn cells() -> Self {
+        Self::new(Pattern::Cells, ColourMap::ice())
+            .with_scale(0.3)
+            .with_speed(0.03)
+            .with_warp(0.5)
+    }
+
+    pub fn with_colours(mut self, colours: ColourMap) -> Self {
+        self.colours = colours;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    pub fn with_octaves(mut self, octaves: u32) -> Self {
+        self.octaves = octaves;
+        self
+    }
+
+    pub fn with_warp(mut self, warp: f32) -> Self {
+        self.warp = warp;
+        self
+    }
+
+    pub fn with_threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u32) -> Self {
+        self.simplex = Simplex::new(seed);
+        self.worley = Worley::new(seed);
+        self
+    }
+
+    fn colour(&self, [x, y, z]: [f32; 3]) -> LinSrgba {
+        let (s, t) = (self.scale, self.time);
+        let fbm = Fbm::new(&self.simplex, self.octaves);
+
+        match self.pattern {
+            Pattern::Plasma => {
+                let v = fbm.sample([x * s, y * s, z * s], t);
+
+                // fbm rarely reaches its extremes, so spread it out and let
+                // the colours wrap
+                let hue = v * 0.7 + 0.5 + t * 0.2;
+                self.colours.at(hue - math::floor(hue))
+            }
+            Pattern::LavaLamp => {
+                let warped = Warp {
+                    field: &fbm,
+                    offset: &self.simplex,
+                    strength: self.warp,
+                };
+
+                // stretched upwards and rising
+                let v = warped.sample([x * s, y * s * 0.6 - t * 0.5, z * s], t);
+
+                let mut colour = self.colours.at(v + 0.5);
+                colour.alpha *= smoothstep(self.threshold - 0.1, self.threshold + 0.1, v);
+                colour
+            }
+            Pattern::Clouds => {
+                let v = fbm.sample([x * s + t, y * s * 1.5, z * s], t * 0.3);
+
+                let density = ((v - self.threshold) / (1.0 - self.threshold)).max(0.0);
+                self.colours.at((density * 2.5).min(1.0))
+            }
+            Pattern::Cells => {
+                let warped = Warp {
+                    field: &self.worley,
+                    offset: &self.simplex,
+                    strength: self.warp,
+                };
+
+                // brightest on the walls, halfway between points
+                let v = warped.sample([x * s, y * s, z * s], t * 10.0);
+                self.colours.at(smoothstep(0.2, 0.6, v))
+            }
+        }
+    }
+}
+
+impl core::fmt::Debug for NoiseField {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("NoiseField")
+            .field("pattern", &self.pattern)
+            .finish()
+    }
+}
+
+impl Animation for NoiseField {
+    fn next_frame(&mut self, frame: &mut Frame) {
+        let this = &*self;
+
+        frame.par_for_each(|x, y, z, pix| {
+            *pix = this.colour([x as f32, y as f32, z as f32]);
+        });
+
+        self.time += self.speed;
+    }
+
+    fn reset(&mut self) {
+        self.time = 0.0;
+    }
+}
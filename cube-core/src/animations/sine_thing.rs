@@ -0,0 +1,38 @@
+use palette::{FromColor, Hsva, RgbHue, Srgba};
+
+use crate::{animation::Animation, frame::Frame, math};
+
+#[derive(Default)]
+#[cfg_attr(feature = "visual", derive(bevy_inspector_egui::Inspectable))]
+pub struct SineThing {
+    step: f32,
+}
+
+impl core::fmt::Debug for SineThing {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SineThing").finish()
+    }
+}
+
+impl Animation for SineThing {
+    fn next_frame(&mut self, frame: &mut Frame) {
+        let step = self.step;
+
+        frame.par_for_each(|x, y, z, pix| {
+            let (dx, dy, dz) = (4.0 - x as f32, 4.0 - y as f32, 4.0 - z as f32);
+            let dist = math::sqrt(dx * dx + dy * dy + dz * dz);
+
+            let alpha = (math::sin(((step * 3.0 + dist) * 40.0).to_radians()) + 1.0) / 2.0;
+            let hue = RgbHue::from_radians(((step + dist) * 30.0).to_radians());
+            let hsv = Hsva::new(hue, 1.0, 1.0, alpha);
+            *pix = Srgba::from_color(hsv).into_linear()
+        });
+
+        self.step += 0.1;
+        self.step %= 360.0 / 30.0;
+    }
+
+    fn reset(&mut self) {
+        self.step = 0.0;
+    }
+}
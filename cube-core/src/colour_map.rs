@@ -0,0 +1,120 @@
+//! Gradients for turning a value into a colour.
+
+use palette::LinSrgba;
+
+const MAX_STOPS: usize = 8;
+
+/// Up to eight colours at positions in `0.0..=1.0`, blended linearly in
+/// between.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColourMap {
+    stops: [(f32, LinSrgba); MAX_STOPS],
+    len: usize,
+}
+
+impl ColourMap {
+    /// `stops` must be sorted by position, any past the eighth are dropped.
+    pub fn new(stops: &[(f32, LinSrgba)]) -> Self {
+        assert!(!stops.is_empty());
+
+        let mut map = Self {
+            stops: [(0.0, LinSrgba::new(0.0, 0.0, 0.0, 0.0)); MAX_STOPS],
+            len: stops.len().min(MAX_STOPS),
+        };
+        map.stops[..map.len].copy_from_slice(&stops[..map.len]);
+        map
+    }
+
+    /// Evenly spaced stops. `colours` mustn't be empty.
+    pub fn even(colours: &[LinSrgba]) -> Self {
+        assert!(!colours.is_empty());
+
+        let mut stops = [(0.0, LinSrgba::new(0.0, 0.0, 0.0, 0.0)); MAX_STOPS];
+        let len = colours.len().min(MAX_STOPS);
+
+        for (i, (stop, &colour)) in stops.iter_mut().zip(colours).enumerate() {
+            *stop = (i as f32 / (len - 1).max(1) as f32, colour);
+        }
+
+        Self::new(&stops[..len])
+    }
+
+    /// Round the colour wheel and back to red, for values which wrap.
+    pub fn rainbow() -> Self {
+        Self::even(&[
+            LinSrgba::new(1.0, 0.0, 0.0, 1.0),
+            LinSrgba::new(1.0, 1.0, 0.0, 1.0),
+            LinSrgba::new(0.0, 1.0, 0.0, 1.0),
+            LinSrgba::new(0.0, 1.0, 1.0, 1.0),
+            LinSrgba::new(0.0, 0.0, 1.0, 1.0),
+            LinSrgba::new(1.0, 0.0, 1.0, 1.0),
+            LinSrgba::new(1.0, 0.0, 0.0, 1.0),
+        ])
+    }
+
+    /// Dark red through orange to yellow.
+    pub fn lava() -> Self {
+        Self::even(&[
+            LinSrgba::new(0.15, 0.0, 0.0, 1.0),
+            LinSrgba::new(0.8, 0.05, 0.0, 1.0),
+            LinSrgba::new(1.0, 0.35, 0.0, 1.0),
+            LinSrgba::new(1.0, 0.85, 0.3, 1.0),
+        ])
+    }
+
+    /// Clear through grey to white, so thin cloud is see-through.
+    pub fn clouds() -> Self {
+        Self::new(&[
+            (0.0, LinSrgba::new(0.3, 0.3, 0.4, 0.0)),
+            (0.4, LinSrgba::new(0.5, 0.5, 0.6, 0.5)),
+            (1.0, LinSrgba::new(1.0, 1.0, 1.0, 1.0)),
+        ])
+    }
+
+    /// Black through deep blue to cyan, like cold metal.
+    pub fn ice() -> Self {
+        Self::even(&[
+            LinSrgba::new(0.0, 0.0, 0.0, 1.0),
+            LinSrgba::new(0.0, 0.05, 0.5, 1.0),
+            LinSrgba::new(0.2, 0.8, 1.0, 1.0),
+        ])
+    }
+
+    /// Blue through clear to red, for values either side of zero mapped
+    /// around the middle.
+    pub fn diverging() -> Self {
+        Self::new(&[
+            (0.0, LinSrgba::new(0.2, 0.4, 1.0, 1.0)),
+            (0.5, LinSrgba::new(0.0, 0.0, 0.0, 0.0)),
+            (1.0, LinSrgba::new(1.0, 0.3, 0.1, 1.0)),
+        ])
+    }
+
+    /// The colour at `t`, clamped to the ends of the map. NaN gets the first
+    /// colour.
+    pub fn at(&self, t: f32) -> LinSrgba {
+        let stops = &self.stops[..self.len];
+
+        let (first, last) = (stops[0], stops[self.len - 1]);
+        if t <= first.0 || t.is_nan() || self.len == 1 {
+            return first.1;
+        }
+        if t >= last.0 {
+            return last.1;
+        }
+
+        let i = stops
+            .iter()
+            .position(|&(pos, _)| pos > t)
+            .unwrap_or(self.len - 1);
+        let ((p0, c0), (p1, c1)) = (stops[i - 1], stops[i]);
+        let f = if p1 > p0 { (t - p0) / (p1 - p0) } else { 0.0 };
+
+        LinSrgba::new(
+            c0.red + (c1.red - c0.red) * f,
+            c0.green + (c1.green - c0.green) * f,
+            c0.blue + (c1.blue - c0.blue) * f,
+            c0.alpha + (c1.alpha - c0.alpha) * f,
+        )
+    }
+}
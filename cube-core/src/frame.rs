@@ -55,4 +55,31 @@ impl Frame {
                 .map(move |(x, z, pix)| (x, y, z, pix))
         })
     }
+
+    /// Call `f` on every voxel, with the same coordinates as `pixels_mut`.
+    /// With the `parallel` feature the layers are shared between threads,
+    /// otherwise this is a plain loop. Each voxel only ever sees its own
+    /// coordinates so both give identical frames.
+    pub fn par_for_each<F>(&mut self, f: F)
+    where
+        F: Fn(u8, u8, u8, &mut LinSrgba) + Send + Sync,
+    {
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+
+            self.0.par_iter_mut().zip(0..8u8).for_each(|(layer, y)| {
+                for (row, x) in layer.iter_mut().zip(0..8u8) {
+                    for (pix, z) in row.iter_mut().zip(0..8u8) {
+                        f(x, y, z, pix);
+                    }
+                }
+            });
+        }
+
+        #[cfg(not(feature = "parallel"))]
+        for (x, y, z, pix) in self.pixels_mut() {
+            f(x, y, z, pix);
+        }
+    }
 }
@@ -0,0 +1,24 @@
+//! The parts of the cube which don't need an operating system: frames,
+//! animations and their combinators. Without the `std` feature this builds
+//! for `no_std` targets, which must call `time::set_time_source` before
+//! running any time based combinators.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+/// `dbg!` on std, nothing otherwise.
+macro_rules! log {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "std")]
+        {
+            dbg!($($arg)*);
+        }
+    };
+}
+
+pub mod animation;
+pub mod animations;
+pub mod colour_map;
+pub mod frame;
+pub mod math;
+pub mod noise;
+pub mod time;
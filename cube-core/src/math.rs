@@ -0,0 +1,21 @@
+//! `f32` functions which live in `std` rather than `core`, backed by `libm`.
+
+#[inline]
+pub fn sqrt(x: f32) -> f32 {
+    libm::sqrtf(x)
+}
+
+#[inline]
+pub fn sin(x: f32) -> f32 {
+    libm::sinf(x)
+}
+
+#[inline]
+pub fn cos(x: f32) -> f32 {
+    libm::cosf(x)
+}
+
+#[inline]
+pub fn floor(x: f32) -> f32 {
+    libm::floorf(x)
+}
@@ -0,0 +1,342 @@
+//! Coherent noise for animated fields: simplex noise in three and four
+//! dimensions, Worley (cellular) noise, and fractal sums and domain warping
+//! built on top of either.
+//!
+//! Patterns are sampled through [`Field`], which takes a point in space and
+//! a time so anything built from these moves smoothly from frame to frame.
+
+use crate::math;
+
+/// Something which can be sampled at a point in space and time.
+pub trait Field {
+    fn sample(&self, p: [f32; 3], t: f32) -> f32;
+}
+
+impl<F: Field + ?Sized> Field for &F {
+    fn sample(&self, p: [f32; 3], t: f32) -> f32 {
+        (**self).sample(p, t)
+    }
+}
+
+/// Shuffles `0..=255` with a small xorshift generator, doubled up so lookups
+/// can add two entries without wrapping.
+fn permutation(seed: u32) -> [u8; 512] {
+    let mut table = [0u8; 512];
+    for (i, v) in table.iter_mut().take(256).enumerate() {
+        *v = i as u8;
+    }
+
+    // xorshift gets stuck on zero
+    let mut state = match seed ^ 0x9E37_79B9 {
+        0 => 0x2545_F491,
+        state => state,
+    };
+    for i in (1..256).rev() {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+
+        table.swap(i, state as usize % (i + 1));
+    }
+
+    for i in 0..256 {
+        table[256 + i] = table[i];
+    }
+
+    table
+}
+
+#[rustfmt::skip]
+const GRAD3: [[f32; 3]; 12] = [
+    [1.0, 1.0, 0.0], [-1.0, 1.0, 0.0], [1.0, -1.0, 0.0], [-1.0, -1.0, 0.0],
+    [1.0, 0.0, 1.0], [-1.0, 0.0, 1.0], [1.0, 0.0, -1.0], [-1.0, 0.0, -1.0],
+    [0.0, 1.0, 1.0], [0.0, -1.0, 1.0], [0.0, 1.0, -1.0], [0.0, -1.0, -1.0],
+];
+
+#[rustfmt::skip]
+const GRAD4: [[f32; 4]; 32] = [
+    [0.0, 1.0, 1.0, 1.0], [0.0, 1.0, 1.0, -1.0], [0.0, 1.0, -1.0, 1.0], [0.0, 1.0, -1.0, -1.0],
+    [0.0, -1.0, 1.0, 1.0], [0.0, -1.0, 1.0, -1.0], [0.0, -1.0, -1.0, 1.0], [0.0, -1.0, -1.0, -1.0],
+    [1.0, 0.0, 1.0, 1.0], [1.0, 0.0, 1.0, -1.0], [1.0, 0.0, -1.0, 1.0], [1.0, 0.0, -1.0, -1.0],
+    [-1.0, 0.0, 1.0, 1.0], [-1.0, 0.0, 1.0, -1.0], [-1.0, 0.0, -1.0, 1.0], [-1.0, 0.0, -1.0, -1.0],
+    [1.0, 1.0, 0.0, 1.0], [1.0, 1.0, 0.0, -1.0], [1.0, -1.0, 0.0, 1.0], [1.0, -1.0, 0.0, -1.0],
+    [-1.0, 1.0, 0.0, 1.0], [-1.0, 1.0, 0.0, -1.0], [-1.0, -1.0, 0.0, 1.0], [-1.0, -1.0, 0.0, -1.0],
+    [1.0, 1.0, 1.0, 0.0], [1.0, 1.0, -1.0, 0.0], [1.0, -1.0, 1.0, 0.0], [1.0, -1.0, -1.0, 0.0],
+    [-1.0, 1.0, 1.0, 0.0], [-1.0, 1.0, -1.0, 0.0], [-1.0, -1.0, 1.0, 0.0], [-1.0, -1.0, -1.0, 0.0],
+];
+
+fn floor(x: f32) -> i32 {
+    math::floor(x) as i32
+}
+
+/// Gustavson's simplex noise, roughly in `-1.0..=1.0`.
+#[derive(Clone)]
+pub struct Simplex {
+    perm: [u8; 512],
+}
+
+impl Default for Simplex {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl core::fmt::Debug for Simplex {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Simplex").finish()
+    }
+}
+
+impl Simplex {
+    /// Different seeds give unrelated patterns.
+    pub fn new(seed: u32) -> Self {
+        Self {
+            perm: permutation(seed),
+        }
+    }
+
+    fn hash(&self, i: i32) -> usize {
+        self.perm[(i & 255) as usize] as usize
+    }
+
+    pub fn noise3(&self, x: f32, y: f32, z: f32) -> f32 {
+        const F3: f32 = 1.0 / 3.0;
+        const G3: f32 = 1.0 / 6.0;
+
+        // which simplex cell the point is in, found in skewed space
+        let s = (x + y + z) * F3;
+        let (i, j, k) = (floor(x + s), floor(y + s), floor(z + s));
+
+        let t = (i + j + k) as f32 * G3;
+        let d0 = [x - (i as f32 - t), y - (j as f32 - t), z - (k as f32 - t)];
+
+        // the middle two corners depend on which axis the point is furthest
+        // along
+        let ([i1, j1, k1], [i2, j2, k2]) = if d0[0] >= d0[1] {
+            if d0[1] >= d0[2] {
+                ([1, 0, 0], [1, 1, 0])
+            } else if d0[0] >= d0[2] {
+                ([1, 0, 0], [1, 0, 1])
+            } else {
+                ([0, 0, 1], [1, 0, 1])
+            }
+        } else if d0[1] < d0[2] {
+            ([0, 0, 1], [0, 1, 1])
+        } else if d0[0] < d0[2] {
+            ([0, 1, 0], [0, 1, 1])
+        } else {
+            ([0, 1, 0], [1, 1, 0])
+        };
+
+        let corners = [
+            ([0, 0, 0], 0.0),
+            ([i1, j1, k1], G3),
+            ([i2, j2, k2], 2.0 * G3),
+            ([1, 1, 1], 3.0 * G3),
+        ];
+
+        let mut total = 0.0;
+
+        for ([ci, cj, ck], offset) in corners {
+            let d = [
+                d0[0] - ci as f32 + offset,
+                d0[1] - cj as f32 + offset,
+                d0[2] - ck as f32 + offset,
+            ];
+
+            let falloff = 0.6 - d[0] * d[0] - d[1] * d[1] - d[2] * d[2];
+            if falloff <= 0.0 {
+                continue;
+            }
+
+            let g =
+                GRAD3[self.hash(i + ci + self.hash(j + cj + self.hash(k + ck) as i32) as i32) % 12];
+            let falloff = falloff * falloff;
+            total += falloff * falloff * (g[0] * d[0] + g[1] * d[1] + g[2] * d[2]);
+        }
+
+        32.0 * total
+    }
+
+    pub fn noise4(&self, x: f32, y: f32, z: f32, w: f32) -> f32 {
+        // (sqrt(5) - 1) / 4 and (5 - sqrt(5)) / 20
+        const F4: f32 = 0.309_017;
+        const G4: f32 = 0.138_196_6;
+
+        let s = (x + y + z + w) * F4;
+        let cell = [floor(x + s), floor(y + s), floor(z + s), floor(w + s)];
+
+        let t = cell.iter().sum::<i32>() as f32 * G4;
+        let p = [x, y, z, w];
+        let d0: [f32; 4] = core::array::from_fn(|a| p[a] - (cell[a] as f32 - t));
+
+        // rank the axes by how far along them the point is, the corners are
+        // then stepped through from the largest axis down
+        let mut rank = [0; 4];
+        for a in 0..4 {
+            for b in a + 1..4 {
+                if d0[a] > d0[b] {
+                    rank[a] += 1;
+                } else {
+                    rank[b] += 1;
+                }
+            }
+        }
+
+        let mut total = 0.0;
+
+        for corner in 0..5 {
+            let step: [i32; 4] = core::array::from_fn(|a| (rank[a] >= 4 - corner) as i32);
+            let offset = corner as f32 * G4;
+            let d: [f32; 4] = core::array::from_fn(|a| d0[a] - step[a] as f32 + offset);
+
+            let falloff = 0.6 - d.iter().map(|c| c * c).sum::<f32>();
+            if falloff <= 0.0 {
+                continue;
+            }
+
+            let h = self.hash(cell[3] + step[3]) as i32;
+            let h = self.hash(cell[2] + step[2] + h) as i32;
+            let h = self.hash(cell[1] + step[1] + h) as i32;
+            let g = GRAD4[self.hash(cell[0] + step[0] + h) % 32];
+
+            let falloff = falloff * falloff;
+            total += falloff * falloff * (0..4).map(|a| g[a] * d[a]).sum::<f32>();
+        }
+
+        27.0 * total
+    }
+}
+
+/// Four dimensional noise with time as the fourth axis, so the pattern
+/// changes in place rather than scrolling.
+impl Field for Simplex {
+    fn sample(&self, [x, y, z]: [f32; 3], t: f32) -> f32 {
+        self.noise4(x, y, z, t)
+    }
+}
+
+/// Fractional Brownian motion: `octaves` copies of `field`, each
+/// `lacunarity` times finer and `gain` times fainter than the last. The sum
+/// is scaled back to the range of `field`.
+#[derive(Clone, Copy, Debug)]
+pub struct Fbm<F> {
+    pub field: F,
+    pub octaves: u32,
+    pub lacunarity: f32,
+    pub gain: f32,
+}
+
+impl<F> Fbm<F> {
+    pub fn new(field: F, octaves: u32) -> Self {
+        Self {
+            field,
+            octaves,
+            lacunarity: 2.0,
+            gain: 0.5,
+        }
+    }
+}
+
+impl<F: Field> Field for Fbm<F> {
+    fn sample(&self, [x, y, z]: [f32; 3], t: f32) -> f32 {
+        let (mut total, mut norm) = (0.0, 0.0);
+        let (mut freq, mut amp) = (1.0, 1.0);
+
+        for _ in 0..self.octaves.max(1) {
+            // finer detail moves faster too
+            total += amp * self.field.sample([x * freq, y * freq, z * freq], t * freq);
+            norm += amp;
+
+            freq *= self.lacunarity;
+            amp *= self.gain;
+        }
+
+        total / norm
+    }
+}
+
+/// Samples `field` at a point pushed around by `offset`, `strength` says how
+/// far, which swirls the pattern.
+#[derive(Clone, Copy, Debug)]
+pub struct Warp<F, G> {
+    pub field: F,
+    pub offset: G,
+    pub strength: f32,
+}
+
+impl<F: Field, G: Field> Field for Warp<F, G> {
+    fn sample(&self, p: [f32; 3], t: f32) -> f32 {
+        // the same field sampled far apart gives a different offset per axis
+        let shifted = |[a, b, c]: [f32; 3]| [p[0] + a, p[1] + b, p[2] + c];
+
+        let q = [
+            self.offset.sample(p, t),
+            self.offset.sample(shifted([5.2, 1.3, 2.8]), t),
+            self.offset.sample(shifted([1.7, 9.2, 3.4]), t),
+        ];
+
+        self.field.sample(shifted(q.map(|c| c * self.strength)), t)
+    }
+}
+
+/// Worley noise, the distance to the nearest of a scattering of points, one
+/// per unit cell. Each point wanders around its cell over time. Values are
+/// roughly in `0.0..=1.0`, zero on the points.
+#[derive(Clone)]
+pub struct Worley {
+    perm: [u8; 512],
+}
+
+impl Default for Worley {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl core::fmt::Debug for Worley {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Worley").finish()
+    }
+}
+
+impl Worley {
+    pub fn new(seed: u32) -> Self {
+        Self {
+            perm: permutation(seed),
+        }
+    }
+
+    /// `0.0..1.0` for each cell and axis.
+    fn hash(&self, [i, j, k]: [i32; 3], axis: usize) -> f32 {
+        let h = |n: i32| self.perm[(n & 255) as usize] as i32;
+        h(i + h(j + h(k + h(axis as i32)))) as f32 / 256.0
+    }
+}
+
+impl Field for Worley {
+    fn sample(&self, p: [f32; 3], t: f32) -> f32 {
+        let cell = p.map(floor);
+        let mut nearest = f32::MAX;
+
+        for n in 0..27 {
+            let c = [
+                cell[0] + n % 3 - 1,
+                cell[1] + (n / 3) % 3 - 1,
+                cell[2] + n / 9 - 1,
+            ];
+
+            let mut dist = 0.0;
+            for axis in 0..3 {
+                let phase = core::f32::consts::TAU * self.hash(c, axis);
+                let point = c[axis] as f32 + 0.5 + 0.4 * math::sin(t + phase);
+
+                dist += (point - p[axis]) * (point - p[axis]);
+            }
+
+            nearest = nearest.min(dist);
+        }
+
+        math::sqrt(nearest)
+    }
+}
@@ -0,0 +1,43 @@
+//! A pluggable monotonic clock for the time based combinators.
+//!
+//! With `std` this defaults to `Instant`, on microcontrollers register a
+//! function reading a hardware timer with `set_time_source`.
+
+use core::sync::atomic::{AtomicPtr, Ordering};
+use core::time::Duration;
+
+/// Returns the time since some fixed point, which must never go backwards.
+pub type TimeSource = fn() -> Duration;
+
+static SOURCE: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+pub fn set_time_source(source: TimeSource) {
+    SOURCE.store(source as *mut (), Ordering::Release);
+}
+
+pub fn now() -> Duration {
+    let source = SOURCE.load(Ordering::Acquire);
+
+    if source.is_null() {
+        return default_source();
+    }
+
+    // SAFETY: the only non-null values ever stored are `TimeSource`s
+    let source = unsafe { core::mem::transmute::<*mut (), TimeSource>(source) };
+    source()
+}
+
+#[cfg(feature = "std")]
+fn default_source() -> Duration {
+    use std::sync::OnceLock;
+    use std::time::Instant;
+
+    static START: OnceLock<Instant> = OnceLock::new();
+
+    START.get_or_init(Instant::now).elapsed()
+}
+
+#[cfg(not(feature = "std"))]
+fn default_source() -> Duration {
+    panic!("no time source, call cube_core::time::set_time_source first")
+}
@@ -0,0 +1,54 @@
+//! Lookups at and past the ends of a colour map, and with values the
+//! animations can produce by accident, like NaN.
+
+use cube_core::colour_map::ColourMap;
+use palette::LinSrgba;
+
+fn red() -> LinSrgba {
+    LinSrgba::new(1.0, 0.0, 0.0, 1.0)
+}
+
+fn blue() -> LinSrgba {
+    LinSrgba::new(0.0, 0.0, 1.0, 1.0)
+}
+
+#[test]
+fn blends_between_stops() {
+    let map = ColourMap::even(&[red(), blue()]);
+
+    assert_eq!(map.at(0.0), red());
+    assert_eq!(map.at(1.0), blue());
+    assert_eq!(map.at(0.25), LinSrgba::new(0.75, 0.0, 0.25, 1.0));
+}
+
+#[test]
+fn clamps_to_the_ends() {
+    let map = ColourMap::new(&[(0.2, red()), (0.8, blue())]);
+
+    assert_eq!(map.at(-1.0), red());
+    assert_eq!(map.at(0.1), red());
+    assert_eq!(map.at(0.9), blue());
+    assert_eq!(map.at(f32::INFINITY), blue());
+}
+
+#[test]
+fn nan_is_the_first_colour() {
+    for map in [ColourMap::even(&[red(), blue()]), ColourMap::rainbow()] {
+        assert_eq!(map.at(f32::NAN), map.at(0.0));
+    }
+}
+
+#[test]
+fn single_stop() {
+    for map in [ColourMap::even(&[red()]), ColourMap::new(&[(0.5, red())])] {
+        for t in [-1.0, 0.0, 0.5, 1.0, f32::NAN] {
+            assert_eq!(map.at(t), red(), "at {}", t);
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn no_colours() {
+    ColourMap::even(&[]);
+}
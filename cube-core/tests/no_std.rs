@@ -0,0 +1,53 @@
+//! Drives the core the way a microcontroller would, run this against the
+//! `no_std` build with `cargo test -p cube-core --no-default-features`.
+
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::time::Duration;
+
+use cube_core::animation::Animation;
+use cube_core::animations::sine_thing::SineThing;
+use cube_core::frame::Frame;
+use cube_core::time;
+
+static MILLIS: AtomicU64 = AtomicU64::new(0);
+
+fn fake_clock() -> Duration {
+    Duration::from_millis(MILLIS.load(Ordering::SeqCst))
+}
+
+fn advance(millis: u64) {
+    MILLIS.fetch_add(millis, Ordering::SeqCst);
+}
+
+#[test]
+fn animations_run_on_a_pluggable_clock() {
+    time::set_time_source(fake_clock);
+
+    let mut anim = SineThing::default()
+        .with_fps(10.0)
+        .with_duration(Duration::from_secs(1));
+    let mut frame = Frame::new();
+
+    // no time has passed, so the fps limiter holds the frame back
+    anim.next_frame(&mut frame);
+    assert!(frame.pixels().all(|(_, _, _, pix)| pix.alpha == 0.0));
+
+    advance(150);
+    anim.next_frame(&mut frame);
+    assert!(frame.pixels().any(|(_, _, _, pix)| pix.alpha > 0.0));
+    assert!(!anim.ended());
+
+    let first = frame.clone();
+    advance(150);
+    anim.next_frame(&mut frame);
+    assert!(first
+        .pixels()
+        .zip(frame.pixels())
+        .any(|(a, b)| a.3.alpha != b.3.alpha));
+
+    advance(1000);
+    assert!(anim.ended());
+
+    anim.reset();
+    assert!(!anim.ended());
+}
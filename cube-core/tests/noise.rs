@@ -0,0 +1,79 @@
+//! Ranges and repeatability of the noise fields, which the animations built
+//! on them rely on to map values straight onto colours.
+
+use cube_core::noise::{Fbm, Field, Simplex, Worley};
+
+/// A spread of points and times, not lined up with the noise lattice.
+fn samples() -> impl Iterator<Item = ([f32; 3], f32)> {
+    (0..2000).map(|i| {
+        let i = i as f32;
+        let p = [
+            (i * 0.37) % 13.0 - 6.5,
+            (i * 1.13) % 9.0,
+            (i * 0.71) % 17.0 - 3.0,
+        ];
+
+        (p, i * 0.013)
+    })
+}
+
+#[test]
+fn simplex_stays_in_range() {
+    let noise = Simplex::new(7);
+
+    for (p, t) in samples() {
+        let v = noise.sample(p, t);
+        assert!((-1.0..=1.0).contains(&v), "{} at {:?}, {}", v, p, t);
+
+        let [x, y, z] = p;
+        let v = noise.noise3(x, y, z);
+        assert!((-1.0..=1.0).contains(&v), "{} at {:?}", v, p);
+    }
+}
+
+#[test]
+fn worley_stays_in_range() {
+    let noise = Worley::new(7);
+    // each point stays within 0.4 of its cell's centre, so the one in the
+    // sample's own cell is never further than this
+    let furthest = 0.9 * 3.0f32.sqrt();
+
+    for (p, t) in samples() {
+        let v = noise.sample(p, t);
+        assert!((0.0..=furthest).contains(&v), "{} at {:?}, {}", v, p, t);
+    }
+}
+
+#[test]
+fn same_seed_same_noise() {
+    let (a, b) = (Simplex::new(42), Simplex::new(42));
+    let (c, d) = (Worley::new(42), Worley::new(42));
+
+    for (p, t) in samples() {
+        assert_eq!(a.sample(p, t), b.sample(p, t));
+        assert_eq!(c.sample(p, t), d.sample(p, t));
+    }
+}
+
+#[test]
+fn different_seeds_differ() {
+    // including the seed which would zero the shuffle's generator
+    let seeds = [0, 1, 0x9E37_79B9];
+    let noises = seeds.map(Simplex::new);
+
+    for (i, a) in noises.iter().enumerate() {
+        for b in &noises[i + 1..] {
+            assert!(samples().any(|(p, t)| a.sample(p, t) != b.sample(p, t)));
+        }
+    }
+}
+
+#[test]
+fn one_octave_of_fbm_is_plain_simplex() {
+    let noise = Simplex::new(3);
+    let fbm = Fbm::new(noise.clone(), 1);
+
+    for (p, t) in samples() {
+        assert_eq!(fbm.sample(p, t), noise.sample(p, t));
+    }
+}
@@ -0,0 +1,26 @@
+//! The parallel path has to give exactly the same frames as the serial one,
+//! run with `cargo test -p cube-core --features parallel`.
+
+#![cfg(feature = "parallel")]
+
+use cube_core::frame::Frame;
+use palette::LinSrgba;
+
+fn shade(x: u8, y: u8, z: u8) -> LinSrgba {
+    let (x, y, z) = (x as f32, y as f32, z as f32);
+
+    LinSrgba::new(x / 7.0, (x * y + z).sin().abs(), z / 7.0, (y * 0.3).cos())
+}
+
+#[test]
+fn par_for_each_matches_pixels_mut() {
+    let mut serial = Frame::new();
+    for (x, y, z, pix) in serial.pixels_mut() {
+        *pix = shade(x, y, z);
+    }
+
+    let mut parallel = Frame::new();
+    parallel.par_for_each(|x, y, z, pix| *pix = shade(x, y, z));
+
+    assert!(serial.pixels().zip(parallel.pixels()).all(|(a, b)| a == b));
+}
@@ -0,0 +1,369 @@
+use nalgebra::{vector, Vector3};
+use palette::LinSrgba;
+use rand::Rng;
+
+use crate::{
+    animation::Animation,
+    frame::Frame,
+    sdf::{render_sdf_with, Bounded, MultiUnion, RenderOptions},
+};
+
+use super::utils::{random_colour, random_rotation};
+
+struct Boid {
+    position: Vector3<f32>,
+    velocity: Vector3<f32>,
+    colour: LinSrgba,
+}
+
+impl Boid {
+    fn random(speed: f32, colour: LinSrgba) -> Self {
+        let mut rng = rand::thread_rng();
+
+        Self {
+            position: vector![
+                rng.gen_range(1.0..6.0),
+                rng.gen_range(1.0..6.0),
+                rng.gen_range(1.0..6.0)
+            ],
+            velocity: random_rotation() * vector![0.0, 0.0, speed],
+            colour,
+        }
+    }
+}
+
+/// Keep `velocity`'s direction with its speed brought into `min..=max`.
+fn clamp_speed(velocity: Vector3<f32>, min: f32, max: f32) -> Vector3<f32> {
+    // the inspector can drag them past each other, which `clamp` panics on
+    let (lo, hi) = (min.min(max), min.max(max));
+    let speed = velocity.norm();
+
+    if speed < f32::EPSILON {
+        vector![lo, 0.0, 0.0]
+    } else {
+        velocity * (speed.clamp(lo, hi) / speed)
+    }
+}
+
+/// A flock steering by separation, alignment and cohesion, turning away from
+/// the sides of the cube and scattering from predators which chase the
+/// nearest boid.
+#[cfg_attr(feature = "visual", derive(bevy_inspector_egui::Inspectable))]
+pub struct Boids {
+    #[cfg_attr(feature = "visual", inspectable(min = 0.0, max = 10.0))]
+    separation: f32,
+    #[cfg_attr(feature = "visual", inspectable(min = 0.0, max = 10.0))]
+    alignment: f32,
+    #[cfg_attr(feature = "visual", inspectable(min = 0.0, max = 10.0))]
+    cohesion: f32,
+    #[cfg_attr(feature = "visual", inspectable(min = 0.0, max = 50.0))]
+    avoid_walls: f32,
+    #[cfg_attr(feature = "visual", inspectable(min = 0.0, max = 50.0))]
+    flee: f32,
+    /// How far a boid sees its flockmates.
+    #[cfg_attr(feature = "visual", inspectable(min = 0.5, max = 8.0))]
+    view_radius: f32,
+    /// Flockmates closer than this are pushed away.
+    #[cfg_attr(feature = "visual", inspectable(min = 0.1, max = 4.0))]
+    personal_space: f32,
+    /// Distance from the sides where boids start to turn.
+    #[cfg_attr(feature = "visual", inspectable(min = 0.0, max = 3.5))]
+    wall_margin: f32,
+    /// Voxels a second.
+    #[cfg_attr(feature = "visual", inspectable(min = 0.1, max = 20.0))]
+    min_speed: f32,
+    #[cfg_attr(feature = "visual", inspectable(min = 0.1, max = 20.0))]
+    max_speed: f32,
+    #[cfg_attr(feature = "visual", inspectable(min = 0.1, max = 20.0))]
+    predator_speed: f32,
+    /// Seconds of movement smeared behind each boid.
+    #[cfg_attr(feature = "visual", inspectable(min = 0.0, max = 1.0))]
+    blur: f32,
+    /// Seconds simulated each frame.
+    #[cfg_attr(feature = "visual", inspectable(min = 0.001, max = 0.1))]
+    time_step: f32,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    boids: Vec<Boid>,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    predators: Vec<Boid>,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    sdf_cache: Vec<Bounded<sdfu::mods::Translate<Vector3<f32>, sdfu::Sphere<f32>>>>,
+}
+
+impl Default for Boids {
+    fn default() -> Self {
+        Self::new(14, 1)
+    }
+}
+
+impl std::fmt::Debug for Boids {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Boids")
+            .field("boids", &self.boids.len())
+            .field("predators", &self.predators.len())
+            .finish()
+    }
+}
+
+impl Boids {
+    pub fn new(boids: usize, predators: usize) -> Self {
+        let mut flock = Self {
+            separation: 3.0,
+            alignment: 1.0,
+            cohesion: 0.6,
+            avoid_walls: 12.0,
+            flee: 20.0,
+            view_radius: 2.5,
+            personal_space: 1.0,
+            wall_margin: 1.5,
+            min_speed: 1.5,
+            max_speed: 4.0,
+            predator_speed: 3.0,
+            blur: 0.25,
+            time_step: 1.0 / 60.0,
+            boids: Vec::new(),
+            predators: Vec::new(),
+            sdf_cache: Vec::new(),
+        };
+
+        flock.spawn(boids, predators);
+        flock
+    }
+
+    fn spawn(&mut self, boids: usize, predators: usize) {
+        let colour = random_colour(0.6, 1.0, 1.0);
+
+        self.boids = (0..boids)
+            .map(|_| Boid::random(self.min_speed, colour))
+            .collect();
+        self.predators = (0..predators)
+            .map(|_| Boid::random(self.predator_speed, LinSrgba::new(1.0, 0.0, 0.0, 1.0)))
+            .collect();
+    }
+
+    fn wall_push(&self, p: Vector3<f32>) -> Vector3<f32> {
+        let max = (Frame::LAYERS - 1) as f32;
+
+        p.map(|c| {
+            if c < self.wall_margin {
+                (self.wall_margin - c) / self.wall_margin
+            } else if c > max - self.wall_margin {
+                -(c - (max - self.wall_margin)) / self.wall_margin
+            } else {
+                0.0
+            }
+        })
+    }
+
+    fn steer(&self, i: usize) -> Vector3<f32> {
+        let boid = &self.boids[i];
+
+        let mut apart = Vector3::zeros();
+        let mut heading = Vector3::zeros();
+        let mut centre = Vector3::zeros();
+        let mut seen = 0;
+
+        for (j, other) in self.boids.iter().enumerate() {
+            let offset = boid.position - other.position;
+            let dist = offset.norm();
+
+            if j == i || dist > self.view_radius {
+                continue;
+            }
+
+            if dist < self.personal_space {
+                // harder the closer they are
+                apart += offset / dist.max(0.05).powi(2);
+            }
+
+            heading += other.velocity;
+            centre += other.position;
+            seen += 1;
+        }
+
+        let mut steer = apart * self.separation + self.wall_push(boid.position) * self.avoid_walls;
+
+        if seen > 0 {
+            let n = seen as f32;
+            steer += (heading / n - boid.velocity) * self.alignment;
+            steer += (centre / n - boid.position) * self.cohesion;
+        }
+
+        for predator in &self.predators {
+            let offset = boid.position - predator.position;
+            let dist = offset.norm();
+
+            if dist < self.view_radius {
+                steer += offset / dist.max(0.05) * (1.0 - dist / self.view_radius) * self.flee;
+            }
+        }
+
+        steer
+    }
+
+    fn chase(&self, predator: &Boid) -> Vector3<f32> {
+        let nearest = self.boids.iter().min_by(|a, b| {
+            let da = (a.position - predator.position).norm_squared();
+            let db = (b.position - predator.position).norm_squared();
+            da.total_cmp(&db)
+        });
+
+        let towards = nearest.map_or(Vector3::zeros(), |prey| prey.position - predator.position);
+
+        towards + self.wall_push(predator.position) * self.avoid_walls
+    }
+
+    fn trail(&mut self, boid_index: usize, predator: bool) {
+        let boid = if predator {
+            &self.predators[boid_index]
+        } else {
+            &self.boids[boid_index]
+        };
+
+        let radius = if predator { 0.55 } else { 0.35 };
+        let steps = if self.blur > 0.0 { 4 } else { 1 };
+
+        // fainter and smaller copies back along the path, for motion blur
+        for step in 0..steps {
+            let t = step as f32 / steps as f32;
+            let mut colour = boid.colour;
+            colour.alpha *= 1.0 - t;
+
+            let (position, r) = (
+                boid.position - boid.velocity * self.blur * t,
+                radius * (1.0 - t * 0.5),
+            );
+            self.sdf_cache.push(Bounded::sphere(position, r, colour));
+        }
+    }
+}
+
+impl Animation for Boids {
+    fn next_frame(&mut self, frame: &mut Frame) {
+        let dt = self.time_step;
+        let max = (Frame::LAYERS - 1) as f32;
+
+        let steering = (0..self.boids.len())
+            .map(|i| self.steer(i))
+            .collect::<Vec<_>>();
+
+        for (boid, steer) in self.boids.iter_mut().zip(steering) {
+            boid.velocity = clamp_speed(boid.velocity + steer * dt, self.min_speed, self.max_speed);
+            boid.position = (boid.position + boid.velocity * dt).map(|c| c.clamp(0.0, max));
+        }
+
+        let chasing = self
+            .predators
+            .iter()
+            .map(|predator| self.chase(predator))
+            .collect::<Vec<_>>();
+
+        for (predator, chase) in self.predators.iter_mut().zip(chasing) {
+            predator.velocity = clamp_speed(
+                predator.velocity + chase * dt,
+                self.predator_speed * 0.5,
+                self.predator_speed,
+            );
+            predator.position =
+                (predator.position + predator.velocity * dt).map(|c| c.clamp(0.0, max));
+        }
+
+        self.sdf_cache.clear();
+
+        for i in 0..self.boids.len() {
+            self.trail(i, false);
+        }
+        for i in 0..self.predators.len() {
+            self.trail(i, true);
+        }
+
+        if self.sdf_cache.is_empty() {
+            frame.zero();
+        } else {
+            let union = MultiUnion::hard(&self.sdf_cache);
+
+            render_sdf_with(union, frame, &RenderOptions::antialiased());
+        }
+    }
+
+    fn reset(&mut self) {
+        let (boids, predators) = (self.boids.len(), self.predators.len());
+        self.spawn(boids, predators);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn boid(position: Vector3<f32>, velocity: Vector3<f32>) -> Boid {
+        Boid {
+            position,
+            velocity,
+            colour: LinSrgba::new(1.0, 1.0, 1.0, 1.0),
+        }
+    }
+
+    #[test]
+    fn speeds_are_clamped() {
+        let v = clamp_speed(vector![0.0, 0.3, 0.4], 1.0, 2.0);
+        assert!((v - vector![0.0, 0.6, 0.8]).norm() < 1e-6);
+
+        let v = clamp_speed(vector![0.0, 30.0, 40.0], 1.0, 2.0);
+        assert!((v - vector![0.0, 1.2, 1.6]).norm() < 1e-6);
+
+        let v = vector![1.0, 1.0, 0.0];
+        assert_eq!(clamp_speed(v, 1.0, 2.0), v);
+        assert_eq!(clamp_speed(v, 2.0, 1.0), v);
+        assert_eq!(
+            clamp_speed(Vector3::zeros(), 1.0, 2.0),
+            vector![1.0, 0.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn walls_push_inwards() {
+        let flock = Boids::new(0, 0);
+
+        assert_eq!(flock.wall_push(vector![3.5, 3.5, 3.5]), Vector3::zeros());
+
+        let push = flock.wall_push(vector![0.0, 3.5, 7.0]);
+        assert_eq!(push, vector![1.0, 0.0, -1.0]);
+    }
+
+    #[test]
+    fn boids_keep_apart_and_flee() {
+        let mut flock = Boids::new(0, 0);
+        let still = Vector3::zeros();
+        flock.boids = vec![
+            boid(vector![3.0, 3.5, 3.5], still),
+            boid(vector![3.5, 3.5, 3.5], still),
+        ];
+
+        // too close, so separation beats cohesion
+        assert!(flock.steer(0).x < 0.0);
+        assert!(flock.steer(1).x > 0.0);
+
+        flock.boids.truncate(1);
+        flock.predators = vec![boid(vector![3.0, 3.5, 4.5], still)];
+        assert!(flock.steer(0).z < 0.0);
+        assert!(flock.chase(&flock.predators[0]).z < 0.0);
+    }
+
+    #[test]
+    fn the_flock_stays_in_the_cube() {
+        let mut flock = Boids::new(10, 1);
+        let mut frame = Frame::new();
+
+        for _ in 0..120 {
+            flock.next_frame(&mut frame);
+
+            for boid in &flock.boids {
+                assert!(boid.position.iter().all(|c| (0.0..=7.0).contains(c)));
+
+                let speed = boid.velocity.norm();
+                assert!(speed > 1.5 - 1e-3 && speed < 4.0 + 1e-3, "{}", speed);
+            }
+        }
+    }
+}
@@ -1,13 +1,12 @@
 use crate::{
     animation::Animation,
     frame::Frame,
-    sdf::{render_sdf, MultiUnion},
+    scene::{BlendOp, Node, Scene},
+    sdf::{render_sdf_with, RenderOptions, Sampling},
 };
-use nalgebra::Vector3;
 use palette::LinSrgba;
 use rand::Rng;
 use rapier3d::prelude::*;
-use sdfu::SDF;
 
 use super::utils::{random_colour, random_rotation};
 
@@ -23,7 +22,8 @@ pub struct Bounce {
     mbjs: MultibodyJointSet,
     ccd: CCDSolver,
     bh: Vec<Ball>,
-    sdf_cache: Vec<sdfu::mods::Translate<Vector3<f32>, sdfu::Sphere<f32>>>,
+    /// A sphere for each ball, in the same order.
+    scene: Scene,
 }
 
 #[cfg(feature = "visual")]
@@ -93,7 +93,7 @@ impl Default for Bounce {
         collider_set.insert(collider);
 
         let mut ball_handles = vec![];
-        let sdf_cache = vec![];
+        let mut scene = Scene::new();
 
         let mut rng = rand::thread_rng();
 
@@ -122,7 +122,12 @@ impl Default for Bounce {
                 .build();
             let ball_body_handle = rigid_body_set.insert(rigid_body);
             collider_set.insert_with_parent(collider, ball_body_handle, &mut rigid_body_set);
-            ball_handles.push(Ball::new(ball_body_handle));
+
+            let ball = Ball::new(ball_body_handle);
+
+            // balls which touch merge into one blob
+            scene.push(Node::sphere(0.5, ball.colour).with_op(BlendOp::SmoothUnion(0.8)));
+            ball_handles.push(ball);
         }
 
         let integration_parameters = IntegrationParameters::default();
@@ -146,7 +151,7 @@ impl Default for Bounce {
             mbjs: multibody_joint_set,
             ccd: ccd_solver,
             bh: ball_handles,
-            sdf_cache,
+            scene,
         }
     }
 }
@@ -168,9 +173,7 @@ impl Animation for Bounce {
             &(),
         );
 
-        self.sdf_cache.clear();
-
-        for ball in &self.bh {
+        for (ball, node) in self.bh.iter().zip(self.scene.nodes_mut()) {
             let ball_pos = *self.rbs[ball.handle].translation();
 
             let linvel = *self.rbs[ball.handle].linvel();
@@ -180,14 +183,14 @@ impl Animation for Bounce {
                 self.rbs[ball.handle].set_linvel(new_linvel, false);
             }
 
-            let sdf = sdfu::Sphere::new(0.5, ball.colour).translate(ball_pos);
-
-            self.sdf_cache.push(sdf);
+            node.transform_mut().translation.vector = ball_pos;
         }
 
-        let union = MultiUnion::hard(&self.sdf_cache);
-
-        render_sdf(union, frame);
+        render_sdf_with(
+            &self.scene,
+            frame,
+            &RenderOptions::default().with_sampling(Sampling::Grid(2)),
+        );
     }
 
     fn reset(&mut self) {
@@ -0,0 +1,276 @@
+use std::collections::VecDeque;
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
+
+use nalgebra::{vector, Vector3};
+use palette::{Blend, FromColor, Hsva, LinSrgba, RgbHue, Srgba};
+
+use crate::{
+    animation::Animation,
+    expr::{Expr, ParseError},
+    frame::Frame,
+    particles::splat,
+};
+
+/// A closed or endlessly winding path through the cube.
+///
+/// Each curve is driven by three angles turning at its three frequencies,
+/// rather than by the frequencies times a parameter, so the frequencies can
+/// change while it is being traced without the head jumping.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Curve {
+    /// A sine wave on each axis.
+    Lissajous { frequencies: [f32; 3] },
+    /// Winds `p` times round a torus while looping `q` times through its
+    /// hole.
+    TorusKnot { p: f32, q: f32 },
+    /// Circles `turns` times for each trip up and back down the cube.
+    Helix { turns: f32 },
+    /// Typed in, in terms of the angles `a`, `b` and `c` and the time `t`.
+    /// Coordinates are in `-1.0..=1.0`.
+    Expression {
+        x: Expr,
+        y: Expr,
+        z: Expr,
+        frequencies: [f32; 3],
+    },
+}
+
+impl Curve {
+    pub const VARIABLES: [&'static str; 4] = ["a", "b", "c", "t"];
+
+    /// E.g. `Curve::expression("sin(a)", "sin(b) * cos(t)", "cos(c)", [3.0, 2.0, 1.0])`.
+    pub fn expression(
+        x: &str,
+        y: &str,
+        z: &str,
+        frequencies: [f32; 3],
+    ) -> Result<Self, ParseError> {
+        Ok(Curve::Expression {
+            x: Expr::parse(x, &Self::VARIABLES)?,
+            y: Expr::parse(y, &Self::VARIABLES)?,
+            z: Expr::parse(z, &Self::VARIABLES)?,
+            frequencies,
+        })
+    }
+
+    fn frequencies(&self) -> [f32; 3] {
+        match *self {
+            Curve::Lissajous { frequencies } => frequencies,
+            Curve::TorusKnot { p, q } => [p, q, 0.0],
+            Curve::Helix { turns } => [turns, 1.0, 0.0],
+            Curve::Expression { frequencies, .. } => frequencies,
+        }
+    }
+
+    /// Roughly in `-1.0..=1.0` on each axis.
+    fn point(&self, [a, b, c]: [f32; 3], t: f32) -> Vector3<f32> {
+        match self {
+            // offset so they don't start out lined up along a diagonal
+            Curve::Lissajous { .. } => {
+                vector![a.sin(), (b + FRAC_PI_2).sin(), (c + FRAC_PI_4).sin()]
+            }
+            Curve::TorusKnot { .. } => {
+                let ring = 0.65 + 0.35 * b.cos();
+                vector![ring * a.cos(), 0.35 * b.sin(), ring * a.sin()]
+            }
+            Curve::Helix { .. } => {
+                // a triangle wave, so it climbs at a steady rate
+                let height = b.sin().asin() / FRAC_PI_2;
+                vector![0.8 * a.cos(), height, 0.8 * a.sin()]
+            }
+            Curve::Expression { x, y, z, .. } => {
+                let vars = [a, b, c, t];
+                vector![x.eval(&vars), y.eval(&vars), z.eval(&vars)]
+            }
+        }
+    }
+}
+
+/// A bright point tracing out a `Curve`, leaving a fading trail. The
+/// frequencies wander a little over time so it never quite repeats.
+#[cfg_attr(feature = "visual", derive(bevy_inspector_egui::Inspectable))]
+pub struct CurveTracer {
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    curve: Curve,
+    /// Radians a frame at a frequency of one.
+    #[cfg_attr(feature = "visual", inspectable(min = 0.0, max = 0.2))]
+    speed: f32,
+    /// How far each frequency wanders, as a fraction of itself.
+    #[cfg_attr(feature = "visual", inspectable(min = 0.0, max = 0.5))]
+    drift: f32,
+    /// Radians a frame of the wandering.
+    #[cfg_attr(feature = "visual", inspectable(min = 0.0, max = 0.01))]
+    drift_speed: f32,
+    /// Voxels from the centre to the edge of the curve.
+    #[cfg_attr(feature = "visual", inspectable(min = 0.5, max = 5.0))]
+    size: f32,
+    /// How much of the trail's brightness is kept each frame.
+    #[cfg_attr(feature = "visual", inspectable(min = 0.5, max = 1.0))]
+    decay: f32,
+    /// Points left in the trail each frame, more keeps fast curves unbroken.
+    #[cfg_attr(feature = "visual", inspectable(min = 1, max = 16))]
+    steps_per_frame: usize,
+    #[cfg_attr(feature = "visual", inspectable(min = 0.0, max = 3.0))]
+    glow_radius: f32,
+    /// Degrees the trail's hue moves each frame.
+    #[cfg_attr(feature = "visual", inspectable(min = 0.0, max = 10.0))]
+    hue_speed: f32,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    angles: [f32; 3],
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    time: f32,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    frames: u32,
+    /// Where the head has been, what colour it was and how bright that
+    /// still is, newest first.
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    trail: VecDeque<(Vector3<f32>, LinSrgba, f32)>,
+}
+
+/// Past this the rest of the trail is too faint to see.
+const MAX_TRAIL: usize = 2048;
+
+impl Default for CurveTracer {
+    fn default() -> Self {
+        Self::new(Curve::Lissajous {
+            frequencies: [3.0, 2.0, 5.0],
+        })
+    }
+}
+
+impl std::fmt::Debug for CurveTracer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CurveTracer")
+            .field("curve", &self.curve)
+            .finish()
+    }
+}
+
+impl CurveTracer {
+    pub fn new(curve: Curve) -> Self {
+        Self {
+            curve,
+            speed: 0.02,
+            drift: 0.1,
+            drift_speed: 0.002,
+            size: 3.5,
+            decay: 0.97,
+            steps_per_frame: 4,
+            glow_radius: 1.5,
+            hue_speed: 0.5,
+            angles: [0.0; 3],
+            time: 0.0,
+            frames: 0,
+            trail: VecDeque::new(),
+        }
+    }
+
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    pub fn with_drift(mut self, drift: f32, speed: f32) -> Self {
+        self.drift = drift;
+        self.drift_speed = speed;
+        self
+    }
+
+    pub fn with_size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn with_decay(mut self, decay: f32) -> Self {
+        self.decay = decay;
+        self
+    }
+
+    pub fn with_glow_radius(mut self, radius: f32) -> Self {
+        self.glow_radius = radius;
+        self
+    }
+
+    /// The curve's frequencies, each nudged by its own slow wave.
+    fn frequencies(&self) -> [f32; 3] {
+        let wander = self.frames as f32 * self.drift_speed;
+        let base = self.curve.frequencies();
+
+        // rates which never line up, so neither does the drift
+        let rates = [1.0, 1.618, 2.414];
+
+        std::array::from_fn(|i| base[i] * (1.0 + (wander * rates[i]).sin() * self.drift))
+    }
+
+    fn head(&self) -> Vector3<f32> {
+        let centre = Vector3::repeat((Frame::LAYERS - 1) as f32 / 2.0);
+        self.curve.point(self.angles, self.time) * self.size + centre
+    }
+}
+
+impl Animation for CurveTracer {
+    fn next_frame(&mut self, frame: &mut Frame) {
+        let frequencies = self.frequencies();
+        let hue = RgbHue::from_degrees(self.frames as f32 * self.hue_speed);
+        let colour = Srgba::from_color(Hsva::new(hue, 1.0, 1.0, 1.0)).into_linear();
+
+        for brightness in self.trail.iter_mut().map(|(_, _, b)| b) {
+            *brightness *= self.decay;
+        }
+
+        let steps = self.steps_per_frame.max(1);
+        for _ in 0..steps {
+            let step = self.speed / steps as f32;
+
+            for (angle, f) in self.angles.iter_mut().zip(frequencies) {
+                *angle += f * step;
+            }
+            self.time += step;
+
+            self.trail.push_front((self.head(), colour, 1.0));
+        }
+
+        while self
+            .trail
+            .back()
+            .is_some_and(|&(_, _, brightness)| brightness < 0.01)
+            || self.trail.len() > MAX_TRAIL
+        {
+            self.trail.pop_back();
+        }
+
+        self.frames += 1;
+
+        frame.zero();
+
+        // spread over the steps, so a busier trail isn't brighter
+        let share = 1.0 / steps as f32;
+        for &(position, mut colour, brightness) in &self.trail {
+            colour.alpha *= brightness * share;
+            splat(frame, position, colour);
+        }
+
+        let head = self.head();
+        let r = self.glow_radius.max(0.01);
+
+        for (x, y, z, pix) in frame.pixels_mut() {
+            let dist = (vector![x as f32, y as f32, z as f32] - head).norm();
+
+            if dist < r {
+                let glow = LinSrgba::new(1.0, 1.0, 1.0, (1.0 - dist / r).powi(2));
+
+                // added like `splat` does, so the head lights up the trail
+                let mut sum = pix.into_premultiplied() + glow.into_premultiplied();
+                sum.alpha = sum.alpha.min(1.0);
+                *pix = LinSrgba::from_premultiplied(sum);
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.angles = [0.0; 3];
+        self.time = 0.0;
+        self.frames = 0;
+        self.trail.clear();
+    }
+}
@@ -0,0 +1,162 @@
+use nalgebra::{vector, Vector3};
+use palette::{LinSrgba, Mix};
+use rand::Rng;
+
+use crate::{
+    animation::Animation,
+    frame::Frame,
+    particles::{splat, ColourOverLife, Emitter, Particle, ParticleSystem},
+};
+
+use super::utils::random_colour;
+
+struct Rocket {
+    body: Particle,
+    trail: Emitter,
+    colour: LinSrgba,
+}
+
+/// Rockets rise from the bottom of the cube leaving sparks behind them and
+/// burst into a ball of coloured sparks at the top of their climb.
+#[cfg_attr(feature = "visual", derive(bevy_inspector_egui::Inspectable))]
+pub struct Fireworks {
+    /// Rockets launched a second, on average.
+    #[cfg_attr(feature = "visual", inspectable(min = 0.0, max = 5.0))]
+    launch_rate: f32,
+    #[cfg_attr(feature = "visual", inspectable(min = 1, max = 300))]
+    burst_size: usize,
+    #[cfg_attr(feature = "visual", inspectable(min = 0.0, max = 10.0))]
+    burst_speed: f32,
+    /// Seconds burst sparks last.
+    #[cfg_attr(feature = "visual", inspectable(min = 0.1, max = 5.0))]
+    spark_life: f32,
+    #[cfg_attr(feature = "visual", inspectable(min = 0.0, max = 20.0))]
+    gravity: f32,
+    #[cfg_attr(feature = "visual", inspectable(min = 0.0, max = 5.0))]
+    drag: f32,
+    /// Seconds simulated each frame.
+    #[cfg_attr(feature = "visual", inspectable(min = 0.001, max = 0.1))]
+    time_step: f32,
+    #[cfg_attr(feature = "visual", inspectable(read_only))]
+    sparks_alive: usize,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    rockets: Vec<Rocket>,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    sparks: ParticleSystem,
+}
+
+impl Default for Fireworks {
+    fn default() -> Self {
+        Self {
+            launch_rate: 0.8,
+            burst_size: 80,
+            burst_speed: 3.5,
+            spark_life: 1.4,
+            gravity: 6.0,
+            drag: 1.5,
+            time_step: 1.0 / 60.0,
+            sparks_alive: 0,
+            rockets: Vec::new(),
+            sparks: ParticleSystem::new(),
+        }
+    }
+}
+
+impl std::fmt::Debug for Fireworks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Fireworks").finish()
+    }
+}
+
+impl Fireworks {
+    fn launch(&mut self) {
+        let mut rng = rand::thread_rng();
+
+        // fast enough to just reach the chosen height
+        let height = rng.gen_range(4.5..6.5);
+        let speed = (2.0 * self.gravity * height).sqrt();
+
+        let position = vector![rng.gen_range(1.5..5.5), 0.0, rng.gen_range(1.5..5.5)];
+        let velocity = vector![rng.gen_range(-0.4..0.4), speed, rng.gen_range(-0.4..0.4)];
+
+        let glow = LinSrgba::new(1.0, 0.6, 0.2, 0.8);
+
+        self.rockets.push(Rocket {
+            body: Particle::new(
+                position,
+                velocity,
+                f32::INFINITY,
+                ColourOverLife::constant(LinSrgba::new(1.0, 0.9, 0.6, 1.0)),
+            ),
+            trail: Emitter::default()
+                .with_direction(-Vector3::y_axis(), 0.6)
+                .with_speed(0.0..1.0)
+                .with_lifetime(0.2..0.5)
+                .with_rate(40.0)
+                .with_colour(ColourOverLife::fading(glow)),
+            colour: random_colour(1.0, 1.0, 1.0),
+        });
+    }
+
+    fn burst(&mut self, at: Vector3<f32>, colour: LinSrgba) {
+        let flash = LinSrgba::new(1.0, 1.0, 1.0, 1.0).mix(&colour, 0.6);
+        let fade = LinSrgba::new(colour.red, colour.green, colour.blue, 0.0);
+
+        Emitter::default()
+            .with_position(at)
+            .with_speed(self.burst_speed * 0.6..self.burst_speed)
+            .with_lifetime(self.spark_life * 0.7..self.spark_life)
+            .with_colour(ColourOverLife::new(flash, fade))
+            .burst(self.burst_size, &mut self.sparks);
+    }
+}
+
+impl Animation for Fireworks {
+    fn next_frame(&mut self, frame: &mut Frame) {
+        let mut rng = rand::thread_rng();
+        let dt = self.time_step;
+        let gravity = vector![0.0, -self.gravity, 0.0];
+
+        if rng.gen_bool((self.launch_rate * dt).clamp(0.0, 1.0) as f64) {
+            self.launch();
+        }
+
+        self.sparks.gravity = gravity;
+        self.sparks.drag = self.drag;
+
+        let mut bursts = Vec::new();
+
+        self.rockets.retain_mut(|rocket| {
+            rocket.body.update(gravity, 0.0, dt);
+            rocket.trail.position = rocket.body.position;
+            rocket.trail.emit(dt, &mut self.sparks);
+
+            if rocket.body.velocity.y <= 0.0 || rocket.body.position.y >= 7.0 {
+                bursts.push((rocket.body.position, rocket.colour));
+                false
+            } else {
+                true
+            }
+        });
+
+        for (at, colour) in bursts {
+            self.burst(at, colour);
+        }
+
+        self.sparks.step(dt);
+        self.sparks_alive = self.sparks.len();
+
+        frame.zero();
+        self.sparks.render(frame);
+
+        for rocket in &self.rockets {
+            splat(frame, rocket.body.position, rocket.body.colour());
+        }
+    }
+
+    fn reset(&mut self) {
+        self.rockets.clear();
+        self.sparks.clear();
+        self.sparks_alive = 0;
+    }
+}
@@ -0,0 +1,376 @@
+use std::collections::VecDeque;
+
+use palette::{FromColor, Hsva, LinSrgba, RgbHue, Srgba};
+use rand::Rng;
+
+use crate::{
+    animation::{Animation, TerminatingAnimation},
+    frame::Frame,
+};
+
+const SIZE: usize = 8;
+const CELLS: usize = SIZE * SIZE * SIZE;
+
+/// Generations remembered when looking for the automaton repeating itself.
+const HISTORY: usize = 16;
+
+/// Age at which cells reach the end of the colour ramp.
+const OLD_AGE: u16 = 32;
+
+const fn range(lo: u32, hi: u32) -> u32 {
+    let mut mask = 0;
+    let mut n = lo;
+
+    while n <= hi {
+        mask |= 1 << n;
+        n += 1;
+    }
+
+    mask
+}
+
+/// An outer totalistic rule over the 26 cells around each cell, as bitmasks
+/// of the neighbour counts at which a live cell survives and a dead one is
+/// born.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rule {
+    survive: u32,
+    birth: u32,
+}
+
+impl Rule {
+    /// Bays' 4555, the closest 3D relative of Conway's life.
+    pub const LIFE_4555: Rule = Rule {
+        survive: range(4, 5),
+        birth: range(5, 5),
+    };
+
+    /// Bays' 5766, slower and more stable than 4555.
+    pub const LIFE_5766: Rule = Rule {
+        survive: range(5, 7),
+        birth: range(6, 6),
+    };
+
+    /// Grows dense blobs out of noise, start it around half full.
+    pub const CLOUDS: Rule = Rule {
+        survive: range(13, 26),
+        birth: range(13, 14) | range(17, 19),
+    };
+
+    pub fn new(survive: &[u8], birth: &[u8]) -> Self {
+        let mask = |counts: &[u8]| counts.iter().fold(0, |acc, &n| acc | 1 << n.min(26));
+
+        Self {
+            survive: mask(survive),
+            birth: mask(birth),
+        }
+    }
+
+    fn next(&self, alive: bool, neighbours: u32) -> bool {
+        let mask = if alive { self.survive } else { self.birth };
+        mask & (1 << neighbours) != 0
+    }
+}
+
+/// What happens to neighbourhoods at the sides of the cube.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Edges {
+    /// Opposite sides are neighbours.
+    Wrap,
+    /// Outside the cube is always dead.
+    Bounded,
+}
+
+fn idx(x: usize, y: usize, z: usize) -> usize {
+    x + SIZE * (y + SIZE * z)
+}
+
+/// 3D life. Live cells shift from cyan to red as they age and dead ones fade
+/// out, the animation ends once everything has died or it starts repeating
+/// itself.
+#[cfg_attr(feature = "visual", derive(bevy_inspector_egui::Inspectable))]
+pub struct Life3D {
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    rule: Rule,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    edges: Edges,
+    /// Chance of each cell starting alive.
+    #[cfg_attr(feature = "visual", inspectable(min = 0.0, max = 1.0))]
+    density: f32,
+    #[cfg_attr(feature = "visual", inspectable(min = 1, max = 60))]
+    frames_per_generation: u32,
+    /// How much of a dead cell's brightness is kept each frame.
+    #[cfg_attr(feature = "visual", inspectable(min = 0.0, max = 1.0))]
+    fade: f32,
+    #[cfg_attr(feature = "visual", inspectable(read_only))]
+    generation: usize,
+    #[cfg_attr(feature = "visual", inspectable(read_only))]
+    population: usize,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    ticks: u32,
+    /// Zero for dead cells, otherwise generations alive.
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    ages: Vec<u16>,
+    /// The age each dead cell died at and how bright it still is.
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    ghosts: Vec<(u16, f32)>,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    history: VecDeque<[u64; SIZE]>,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    finished: bool,
+}
+
+impl Default for Life3D {
+    fn default() -> Self {
+        Self::new(Rule::LIFE_4555, Edges::Wrap)
+    }
+}
+
+impl Life3D {
+    pub fn new(rule: Rule, edges: Edges) -> Self {
+        let mut life = Self {
+            rule,
+            edges,
+            density: 0.25,
+            frames_per_generation: 6,
+            fade: 0.85,
+            generation: 0,
+            population: 0,
+            ticks: 0,
+            ages: vec![0; CELLS],
+            ghosts: vec![(0, 0.0); CELLS],
+            history: VecDeque::with_capacity(HISTORY),
+            finished: false,
+        };
+
+        life.seed();
+        life
+    }
+
+    pub fn with_density(mut self, density: f32) -> Self {
+        self.density = density;
+        self.seed();
+        self
+    }
+
+    pub fn with_frames_per_generation(mut self, frames: u32) -> Self {
+        self.frames_per_generation = frames;
+        self
+    }
+
+    pub fn with_fade(mut self, fade: f32) -> Self {
+        self.fade = fade;
+        self
+    }
+
+    fn seed(&mut self) {
+        let mut rng = rand::thread_rng();
+
+        for age in &mut self.ages {
+            *age = rng.gen_bool(self.density.clamp(0.0, 1.0) as f64) as u16;
+        }
+
+        self.ghosts.fill((0, 0.0));
+        self.history.clear();
+        self.generation = 0;
+        self.population = self.ages.iter().filter(|&&age| age > 0).count();
+        self.ticks = 0;
+        self.finished = self.population == 0;
+    }
+
+    fn neighbours(&self, x: usize, y: usize, z: usize) -> u32 {
+        let mut count = 0;
+
+        for dz in -1..=1isize {
+            for dy in -1..=1isize {
+                for dx in -1..=1isize {
+                    if (dx, dy, dz) == (0, 0, 0) {
+                        continue;
+                    }
+
+                    let coords = [(x, dx), (y, dy), (z, dz)].map(|(c, d)| {
+                        let c = c as isize + d;
+
+                        match self.edges {
+                            Edges::Wrap => Some(c.rem_euclid(SIZE as isize) as usize),
+                            Edges::Bounded => usize::try_from(c).ok().filter(|&c| c < SIZE),
+                        }
+                    });
+
+                    if let [Some(nx), Some(ny), Some(nz)] = coords {
+                        count += (self.ages[idx(nx, ny, nz)] > 0) as u32;
+                    }
+                }
+            }
+        }
+
+        count
+    }
+
+    fn step(&mut self) {
+        let mut next = vec![0; CELLS];
+
+        for z in 0..SIZE {
+            for y in 0..SIZE {
+                for x in 0..SIZE {
+                    let i = idx(x, y, z);
+                    let age = self.ages[i];
+
+                    if self.rule.next(age > 0, self.neighbours(x, y, z)) {
+                        next[i] = age.saturating_add(1);
+                    } else if age > 0 {
+                        self.ghosts[i] = (age, 1.0);
+                    }
+                }
+            }
+        }
+
+        self.ages = next;
+        self.generation += 1;
+        self.population = self.ages.iter().filter(|&&age| age > 0).count();
+
+        // which cells are alive, ignoring age, so oscillators are caught
+        let mut alive = [0u64; SIZE];
+        for (i, &age) in self.ages.iter().enumerate() {
+            alive[i / 64] |= ((age > 0) as u64) << (i % 64);
+        }
+
+        if self.population == 0 || self.history.contains(&alive) {
+            dbg!("life has stagnated", self.generation, self.population);
+            self.finished = true;
+        }
+
+        if self.history.len() == HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back(alive);
+    }
+
+    fn colour(age: u16, brightness: f32) -> LinSrgba {
+        let t = age.min(OLD_AGE) as f32 / OLD_AGE as f32;
+        let hue = RgbHue::from_degrees(180.0 * (1.0 - t));
+
+        Srgba::from_color(Hsva::new(hue, 1.0, 1.0, brightness)).into_linear()
+    }
+}
+
+impl std::fmt::Debug for Life3D {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Life3D")
+            .field("rule", &self.rule)
+            .field("edges", &self.edges)
+            .finish()
+    }
+}
+
+impl Animation for Life3D {
+    fn next_frame(&mut self, frame: &mut Frame) {
+        self.ticks += 1;
+
+        if self.ticks >= self.frames_per_generation {
+            self.ticks = 0;
+
+            if !self.finished {
+                self.step();
+            }
+        }
+
+        for ghost in &mut self.ghosts {
+            ghost.1 *= self.fade;
+        }
+
+        for (x, y, z, pix) in frame.pixels_mut() {
+            let i = idx(x as usize, y as usize, z as usize);
+
+            *pix = match (self.ages[i], self.ghosts[i]) {
+                (0, (age, brightness)) => Self::colour(age, brightness),
+                (age, _) => Self::colour(age, 1.0),
+            };
+        }
+    }
+
+    fn reset(&mut self) {
+        self.seed();
+    }
+
+    fn ended(&self) -> bool {
+        self.finished
+    }
+}
+
+impl TerminatingAnimation for Life3D {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty(rule: Rule, edges: Edges) -> Life3D {
+        Life3D::new(rule, edges).with_density(0.0)
+    }
+
+    #[test]
+    fn rules() {
+        assert_eq!(Rule::new(&[4, 5], &[5]), Rule::LIFE_4555);
+
+        let rule = Rule::LIFE_4555;
+        assert!(!rule.next(true, 3));
+        assert!(rule.next(true, 4));
+        assert!(rule.next(true, 5));
+        assert!(!rule.next(true, 6));
+        assert!(!rule.next(false, 4));
+        assert!(rule.next(false, 5));
+    }
+
+    #[test]
+    fn edges() {
+        for (edges, corner, middle) in [(Edges::Wrap, 26, 26), (Edges::Bounded, 7, 26)] {
+            let mut life = empty(Rule::LIFE_4555, edges).with_density(1.0);
+
+            assert_eq!(life.neighbours(0, 0, 0), corner, "{:?}", edges);
+            assert_eq!(life.neighbours(3, 4, 5), middle, "{:?}", edges);
+
+            // a lone cell in the far corner is only next to the near one
+            // when wrapping
+            life.ages.fill(0);
+            life.ages[idx(7, 7, 7)] = 1;
+            assert_eq!(life.neighbours(0, 0, 0), (edges == Edges::Wrap) as u32);
+        }
+    }
+
+    #[test]
+    fn cells_are_born_and_die() {
+        let mut life = empty(Rule::LIFE_4555, Edges::Bounded);
+
+        // five cells around (3, 3, 3), which is born, while each of them
+        // has too few neighbours to survive
+        for (x, y) in [(2, 3), (4, 3), (3, 2), (3, 4), (2, 2)] {
+            life.ages[idx(x, y, 3)] = 1;
+        }
+        life.step();
+
+        assert_eq!(life.ages[idx(3, 3, 3)], 1);
+        assert_eq!(life.ages[idx(2, 3, 3)], 0);
+        assert_eq!(life.ghosts[idx(2, 3, 3)], (1, 1.0));
+    }
+
+    #[test]
+    fn stops_when_it_repeats() {
+        let everything = (0..=26).collect::<Vec<_>>();
+        let mut life = empty(Rule::new(&everything, &[]), Edges::Wrap);
+
+        life.ages[idx(1, 2, 3)] = 1;
+        life.finished = false;
+        life.step();
+        assert!(!life.finished);
+        assert_eq!(life.ages[idx(1, 2, 3)], 2);
+
+        life.step();
+        assert!(life.finished);
+
+        let mut life = empty(Rule::LIFE_4555, Edges::Wrap);
+        life.ages[idx(1, 2, 3)] = 1;
+        life.step();
+        assert_eq!(life.population, 0);
+        assert!(life.finished);
+    }
+}
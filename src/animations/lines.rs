@@ -2,7 +2,7 @@ use crate::{
     animation::Animation,
     animations::utils::{random_colour, random_rotation},
     frame::Frame,
-    sdf::{render_sdf, MultiUnion},
+    sdf::{render_sdf_with, Bounded, MultiSubtraction, MultiUnion, RenderOptions},
 };
 use nalgebra::{vector, Rotation3, Vector3};
 use palette::LinSrgba;
@@ -14,8 +14,15 @@ pub struct SpinningLines {
     lines: Vec<(Rotation3<f32>, Vector3<f32>, LinSrgba)>,
 
     line_cache: Vec<sdfu::Line<f32, Vector3<f32>>>,
+
+    /// Radius of a hollow sphere for the lines to drill through, rather than
+    /// being drawn themselves.
+    sphere: Option<f32>,
 }
 
+/// How wide the holes drilled into the sphere are.
+const DRILL_RADIUS: f32 = 0.8;
+
 #[cfg(feature = "visual")]
 impl bevy_inspector_egui::Inspectable for SpinningLines {
     type Attributes = <() as bevy_inspector_egui::Inspectable>::Attributes;
@@ -36,6 +43,13 @@ impl std::fmt::Debug for SpinningLines {
     }
 }
 
+impl SpinningLines {
+    pub fn with_sphere(mut self, radius: f32) -> Self {
+        self.sphere = Some(radius);
+        self
+    }
+}
+
 impl Animation for SpinningLines {
     fn next_frame(&mut self, frame: &mut Frame) {
         let mut rng = rand::thread_rng();
@@ -78,6 +92,12 @@ impl Animation for SpinningLines {
             line.0 = Rotation3::from_axis_angle(&Vector3::y_axis(), 0.01) * line.0;
         }
 
+        let thickness = if self.sphere.is_some() {
+            DRILL_RADIUS
+        } else {
+            0.0
+        };
+
         self.line_cache.clear();
         self.line_cache
             .extend(self.lines.iter().cloned().map(|(rot, trans, colour)| {
@@ -86,12 +106,28 @@ impl Animation for SpinningLines {
                 let start = rot * (vector![0.0, 0.0, 100.0] + trans) + base_trans;
                 let end = rot * (vector![0.0, 0.0, -100.0] + trans) + base_trans;
 
-                sdfu::Line::new(start, end, 0.0, colour)
+                sdfu::Line::new(start, end, thickness, colour)
             }));
 
-        let union = MultiUnion::hard(&self.line_cache);
+        match self.sphere {
+            Some(radius) => {
+                let centre = Vector3::repeat((Frame::LAYERS - 1) as f32 / 2.0);
+                let sphere = Bounded::sphere(centre, radius, LinSrgba::new(0.5, 0.5, 0.5, 1.0));
+
+                // just the skin, so the holes show on the far side too, lined
+                // with the colour of the line that made them
+                let drilled = MultiSubtraction::smooth(sphere, &self.line_cache, 0.5).shell(0.4);
 
-        render_sdf(union, frame);
+                render_sdf_with(drilled, frame, &RenderOptions::antialiased());
+            }
+            None => {
+                let union = MultiUnion::hard(&self.line_cache);
+
+                // the lines have no thickness, so sampling only voxel centres
+                // flickers
+                render_sdf_with(union, frame, &RenderOptions::antialiased());
+            }
+        }
     }
 
     fn reset(&mut self) {
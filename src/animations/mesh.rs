@@ -0,0 +1,110 @@
+use nalgebra::{vector, Rotation3, Unit, Vector3};
+use palette::LinSrgba;
+
+use crate::{animation::Animation, frame::Frame, import::mesh::Voxels};
+
+/// Turns voxelized meshes about any number of axes at once. Each cube voxel
+/// is sampled from the fine grid, so its alpha is how much of it the shape
+/// covers at the current angle.
+#[cfg_attr(feature = "visual", derive(bevy_inspector_egui::Inspectable))]
+pub struct MeshViewer {
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    voxels: Voxels,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    colour: LinSrgba,
+    /// (axis, radians per frame)
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    spins: Vec<(Unit<Vector3<f32>>, f32)>,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    start: Rotation3<f32>,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    rotation: Rotation3<f32>,
+    /// Offsets of the samples within a cube voxel.
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    samples: Vec<Vector3<f32>>,
+}
+
+impl MeshViewer {
+    pub fn new(voxels: Voxels, colour: LinSrgba) -> Self {
+        // one sample per fine cell, the grid is scaled to the cube
+        let n = (voxels.resolution() / Frame::LAYERS).clamp(1, 4);
+
+        let samples = (0..n * n * n)
+            .map(|i| {
+                let cell = vector![i % n, (i / n) % n, i / (n * n)];
+                cell.map(|c| (c as f32 + 0.5) / n as f32 - 0.5)
+            })
+            .collect();
+
+        Self {
+            voxels,
+            colour,
+            spins: Vec::new(),
+            start: Rotation3::identity(),
+            rotation: Rotation3::identity(),
+            samples,
+        }
+    }
+
+    /// Add a spin of `speed` radians per frame about `axis`, spins about
+    /// several axes tumble the shape.
+    pub fn spinning(mut self, axis: Unit<Vector3<f32>>, speed: f32) -> Self {
+        self.spins.push((axis, speed));
+        self
+    }
+
+    /// Start from `rotation` rather than how the mesh was loaded.
+    pub fn rotated(mut self, rotation: Rotation3<f32>) -> Self {
+        self.start = rotation;
+        self.rotation = rotation;
+        self
+    }
+
+    fn covered(&self, p: Vector3<f32>) -> bool {
+        let res = self.voxels.resolution();
+        let fine = (p + Vector3::repeat(0.5)) * (res as f32 / Frame::LAYERS as f32);
+
+        if fine.iter().any(|&c| !(0.0..res as f32).contains(&c)) {
+            return false;
+        }
+
+        self.voxels
+            .get(fine.x as usize, fine.y as usize, fine.z as usize)
+    }
+}
+
+impl std::fmt::Debug for MeshViewer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MeshViewer").finish()
+    }
+}
+
+impl Animation for MeshViewer {
+    fn next_frame(&mut self, frame: &mut Frame) {
+        for (axis, speed) in &self.spins {
+            self.rotation = Rotation3::from_axis_angle(axis, *speed) * self.rotation;
+        }
+
+        let centre = vector![3.5, 3.5, 3.5];
+        let inverse = self.rotation.inverse();
+        let this = &*self;
+
+        frame.par_for_each(|x, y, z, pix| {
+            let p = vector![x as f32, y as f32, z as f32];
+
+            let covered = this
+                .samples
+                .iter()
+                .filter(|offset| this.covered(inverse * (p + *offset - centre) + centre))
+                .count();
+
+            let mut colour = this.colour;
+            colour.alpha *= covered as f32 / this.samples.len() as f32;
+            *pix = colour;
+        });
+    }
+
+    fn reset(&mut self) {
+        self.rotation = self.start;
+    }
+}
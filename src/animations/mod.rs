@@ -1,19 +1,38 @@
 use std::time::Duration;
 
-use crate::animation::{MaybeTerminatingAnimation, Animation};
+use crate::animation::Animation;
+use crate::font::{Gradient, Surface, TextStyle};
+use cube_core::colour_map::ColourMap;
 
+pub mod boids;
 pub mod bounce;
+pub mod curves;
+pub mod fireworks;
+pub mod life;
 pub mod lines;
-pub mod sine_thing;
+pub mod mesh;
+pub mod model;
+pub mod polyhedra;
+pub mod rain;
+pub mod sand;
+pub use cube_core::animations::{noise_field, sine_thing};
+pub mod snake;
+pub mod text;
 pub mod waves;
 mod utils;
 
+/// What the playlist needs from an animation, the preview also inspects it.
 #[cfg(feature = "visual")]
-type Anim = impl MaybeTerminatingAnimation + bevy_inspector_egui::Inspectable;
+pub trait Anim: Animation + bevy_inspector_egui::Inspectable {}
+#[cfg(feature = "visual")]
+impl<T: Animation + bevy_inspector_egui::Inspectable> Anim for T {}
+
 #[cfg(not(feature = "visual"))]
-type Anim = impl MaybeTerminatingAnimation;
+pub trait Anim: Animation {}
+#[cfg(not(feature = "visual"))]
+impl<T: Animation> Anim for T {}
 
-pub fn current_config() -> Anim {
+pub fn current_config() -> impl Anim {
     let anim = bounce::Bounce::default()
         .with_fps(60.0)
         .with_duration(Duration::from_secs(60))
@@ -25,17 +44,115 @@ pub fn current_config() -> Anim {
                .with_fps(60.0)
                 .with_duration(Duration::from_secs(60 * 30))
         )
+        .chain(
+            waves::Waves::new(waves::Mode::Wave)
+                .with_fps(60.0)
+                .with_duration(Duration::from_secs(60 * 5)),
+        )
+        .chain(
+            waves::Waves::new(waves::Mode::Heat)
+                .with_boundary(waves::Boundary::Absorbing)
+                .with_colours(ColourMap::lava())
+                .with_fps(60.0)
+                .with_duration(Duration::from_secs(60 * 5)),
+        )
         .chain(
             lines::SpinningLines::default()
                 .with_fps(60.0)
                 .with_duration(Duration::from_secs(60 * 30)),
                 // .with_duration(Duration::from_secs(10)),
         )
+        .chain(
+            lines::SpinningLines::default()
+                .with_sphere(3.0)
+                .with_fps(60.0)
+                .with_duration(Duration::from_secs(60 * 5)),
+        )
+        .chain(
+            polyhedra::Polyhedra::default()
+                .with_fps(60.0)
+                .with_duration(Duration::from_secs(60 * 5)),
+        )
         .chain(
             sine_thing::SineThing::default()
                 .with_fps(60.0)
                 .with_duration(Duration::from_secs(60 * 30)),
                 // .with_duration(Duration::from_secs(10)),
+        )
+        .chain(
+            noise_field::NoiseField::plasma()
+                .with_fps(60.0)
+                .with_duration(Duration::from_secs(60 * 5)),
+        )
+        .chain(
+            noise_field::NoiseField::lava_lamp()
+                .with_fps(60.0)
+                .with_duration(Duration::from_secs(60 * 5)),
+        )
+        .chain(
+            noise_field::NoiseField::clouds()
+                .with_fps(60.0)
+                .with_duration(Duration::from_secs(60 * 5)),
+        )
+        .chain(
+            curves::CurveTracer::default()
+                .with_fps(60.0)
+                .with_duration(Duration::from_secs(60 * 3)),
+        )
+        .chain(
+            curves::CurveTracer::new(curves::Curve::TorusKnot { p: 2.0, q: 3.0 })
+                .with_fps(60.0)
+                .with_duration(Duration::from_secs(60 * 3)),
+        )
+        .chain(
+            curves::CurveTracer::new(
+                curves::Curve::expression(
+                    "sin(a) * cos(b)",
+                    "sin(b)",
+                    "cos(a) * cos(b)",
+                    [5.0, 1.0, 0.0],
+                )
+                .expect("spiral should parse"),
+            )
+            .with_fps(60.0)
+            .with_duration(Duration::from_secs(60 * 3)),
+        )
+        .chain(
+            fireworks::Fireworks::default()
+                .with_fps(60.0)
+                .with_duration(Duration::from_secs(60 * 5)),
+        )
+        .chain(
+            rain::Rain::default()
+                .with_fps(60.0)
+                .with_duration(Duration::from_secs(60 * 5)),
+        )
+        .chain(
+            boids::Boids::default()
+                .with_fps(60.0)
+                .with_duration(Duration::from_secs(60 * 5)),
+        )
+        .chain(
+            life::Life3D::default()
+                .with_fps(60.0)
+                .with_duration(Duration::from_secs(60 * 2))
+                .repeat(5),
+        )
+        .chain(
+            snake::Snake::default()
+                .with_fps(60.0)
+                .repeat(5),
+        )
+        .chain(
+            sand::Sand::default()
+                .with_fps(60.0),
+        )
+        .chain(
+            text::TextScroller::new("HAPPY BIRTHDAY ♥")
+                .on(Surface::Ring)
+                .with_style(TextStyle::default().with_colour(Gradient::Rainbow).with_depth(2))
+                .with_fps(60.0)
+                .repeat(3),
         );
 
     println!("{:#?}", anim);
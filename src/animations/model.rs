@@ -0,0 +1,130 @@
+use std::path::Path;
+
+use nalgebra::{vector, Rotation3, Unit, Vector3};
+
+use crate::{
+    animation::{Animation, FixedFPSAnimation},
+    frame::Frame,
+    import::{
+        slices::{self, SliceOptions},
+        ImportError,
+    },
+    volume::{self, Volume},
+};
+
+/// Shows imported models, either still, spinning about an axis through the
+/// centre of the cube, or stepping through them as the frames of an
+/// animation. Spinning and playing can be combined.
+#[cfg_attr(feature = "visual", derive(bevy_inspector_egui::Inspectable))]
+pub struct ModelViewer {
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    frames: Vec<Frame>,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    axis: Unit<Vector3<f32>>,
+    /// Radians turned per frame.
+    #[cfg_attr(feature = "visual", inspectable(min = -0.5, max = 0.5))]
+    spin_speed: f32,
+    /// How many frames each model is shown for while playing, zero holds the
+    /// current model.
+    #[cfg_attr(feature = "visual", inspectable(min = 0, max = 120))]
+    hold: u32,
+    #[cfg_attr(feature = "visual", inspectable(read_only))]
+    current: usize,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    ticks: u32,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    angle: f32,
+}
+
+impl ModelViewer {
+    /// Every volume is scaled to fit the cube.
+    pub fn new(models: &[Volume]) -> Self {
+        Self::from_frames(models.iter().map(Volume::fit).collect())
+    }
+
+    pub fn from_frames(frames: Vec<Frame>) -> Self {
+        assert!(!frames.is_empty());
+
+        Self {
+            frames,
+            axis: Vector3::y_axis(),
+            spin_speed: 0.0,
+            hold: 0,
+            current: 0,
+            ticks: 0,
+            angle: 0.0,
+        }
+    }
+
+    /// Play a directory of image slices as a video at `fps` volumes a
+    /// second, see `import::slices` for how they're grouped.
+    pub fn play_slices(
+        dir: impl AsRef<Path>,
+        options: &SliceOptions,
+        fps: f32,
+    ) -> Result<FixedFPSAnimation<Self>, ImportError> {
+        let volumes = slices::load_dir(dir, options)?;
+
+        if volumes.is_empty() {
+            return Err(ImportError::Format("no slices found".to_owned()));
+        }
+
+        let frames = volumes.iter().map(|v| options.scale(v)).collect();
+
+        Ok(Self::from_frames(frames).playing(1).with_fps(fps))
+    }
+
+    pub fn spinning(mut self, axis: Unit<Vector3<f32>>, speed: f32) -> Self {
+        self.axis = axis;
+        self.spin_speed = speed;
+        self
+    }
+
+    pub fn playing(mut self, hold: u32) -> Self {
+        self.hold = hold;
+        self
+    }
+}
+
+impl std::fmt::Debug for ModelViewer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ModelViewer")
+            .field("frames", &self.frames.len())
+            .finish()
+    }
+}
+
+impl Animation for ModelViewer {
+    fn next_frame(&mut self, frame: &mut Frame) {
+        let model = &self.frames[self.current];
+
+        if self.angle == 0.0 {
+            frame.clone_from(model);
+        } else {
+            let centre = vector![3.5, 3.5, 3.5];
+            let inverse = Rotation3::from_axis_angle(&self.axis, -self.angle);
+
+            frame.par_for_each(|x, y, z, pix| {
+                let p = vector![x as f32, y as f32, z as f32];
+                *pix = volume::sample(model, inverse * (p - centre) + centre);
+            });
+        }
+
+        self.angle = (self.angle + self.spin_speed) % std::f32::consts::TAU;
+
+        if self.hold > 0 {
+            self.ticks += 1;
+
+            if self.ticks >= self.hold {
+                self.ticks = 0;
+                self.current = (self.current + 1) % self.frames.len();
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.current = 0;
+        self.ticks = 0;
+        self.angle = 0.0;
+    }
+}
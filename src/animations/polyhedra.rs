@@ -0,0 +1,361 @@
+use nalgebra::{vector, UnitQuaternion, Vector3};
+use palette::{FromColor, Hsva, LinSrgba, Mix, RgbHue, Srgba};
+use rand::Rng;
+
+use crate::{
+    animation::Animation,
+    animations::utils::random_rotation,
+    frame::Frame,
+    sdf::{render_sdf_with, MultiUnion, RenderOptions},
+};
+
+/// The golden ratio, which the dodecahedron and icosahedron are built from.
+const PHI: f32 = 1.618_034;
+
+/// The ends of an edge.
+type Edge = (Vector3<f32>, Vector3<f32>);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Solid {
+    Tetrahedron,
+    Cube,
+    Octahedron,
+    Dodecahedron,
+    Icosahedron,
+}
+
+impl Solid {
+    pub const ALL: [Solid; 5] = [
+        Solid::Tetrahedron,
+        Solid::Cube,
+        Solid::Octahedron,
+        Solid::Dodecahedron,
+        Solid::Icosahedron,
+    ];
+
+    fn vertices(self) -> Vec<Vector3<f32>> {
+        // every combination of signs on the non-zero coordinates of `v`
+        let signs = |v: [f32; 3]| {
+            (0..8)
+                .filter(move |bits| (0..3).all(|a| v[a] != 0.0 || bits & (1 << a) == 0))
+                .map(move |bits| {
+                    let sign = |a: usize| if bits & (1 << a) == 0 { 1.0 } else { -1.0 };
+                    vector![v[0] * sign(0), v[1] * sign(1), v[2] * sign(2)]
+                })
+        };
+        // the three ways round the axes
+        let cycle = |[a, b, c]: [f32; 3]| [[a, b, c], [c, a, b], [b, c, a]];
+
+        let mut vertices = match self {
+            Solid::Tetrahedron => vec![
+                vector![1.0, 1.0, 1.0],
+                vector![1.0, -1.0, -1.0],
+                vector![-1.0, 1.0, -1.0],
+                vector![-1.0, -1.0, 1.0],
+            ],
+            Solid::Cube => signs([1.0, 1.0, 1.0]).collect(),
+            Solid::Octahedron => cycle([1.0, 0.0, 0.0]).into_iter().flat_map(signs).collect(),
+            Solid::Dodecahedron => signs([1.0, 1.0, 1.0])
+                .chain(cycle([0.0, 1.0 / PHI, PHI]).into_iter().flat_map(signs))
+                .collect(),
+            Solid::Icosahedron => cycle([0.0, 1.0, PHI]).into_iter().flat_map(signs).collect(),
+        };
+
+        for v in &mut vertices {
+            *v = v.normalize();
+        }
+
+        vertices
+    }
+
+    /// Each edge's ends, on a sphere of radius one.
+    fn edges(self) -> Vec<Edge> {
+        let vertices = self.vertices();
+        let pairs = || {
+            vertices
+                .iter()
+                .enumerate()
+                .flat_map(|(i, a)| vertices[i + 1..].iter().map(move |b| (*a, *b)))
+        };
+
+        // every edge of a platonic solid is the same length, and no two
+        // corners are closer than that
+        let shortest = pairs()
+            .map(|(a, b)| (a - b).norm())
+            .fold(f32::MAX, f32::min);
+
+        pairs()
+            .filter(|(a, b)| (a - b).norm() < shortest * 1.01)
+            .collect()
+    }
+
+    fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|&s| s == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+}
+
+fn smoothstep(t: f32) -> f32 {
+    let t = t.clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// A colour for each edge, evenly spaced round the colour wheel from `hue`.
+fn edge_colours(count: usize, hue: f32) -> Vec<LinSrgba> {
+    (0..count)
+        .map(|i| {
+            let hue = RgbHue::from_degrees(hue + 360.0 * i as f32 / count as f32);
+            Srgba::from_color(Hsva::new(hue, 1.0, 1.0, 1.0)).into_linear()
+        })
+        .collect()
+}
+
+/// Wireframe platonic solids tumbling around the middle of the cube, each
+/// folding into the next every so often.
+#[cfg_attr(feature = "visual", derive(bevy_inspector_egui::Inspectable))]
+pub struct Polyhedra {
+    /// Distance from the centre to the corners, in voxels.
+    #[cfg_attr(feature = "visual", inspectable(min = 1.0, max = 6.0))]
+    size: f32,
+    #[cfg_attr(feature = "visual", inspectable(min = 0.0, max = 1.0))]
+    thickness: f32,
+    /// Frames spent turning towards each new orientation.
+    #[cfg_attr(feature = "visual", inspectable(min = 1, max = 600))]
+    tumble_frames: u32,
+    /// Frames each solid is shown for before morphing.
+    #[cfg_attr(feature = "visual", inspectable(min = 1, max = 3600))]
+    hold_frames: u32,
+    #[cfg_attr(feature = "visual", inspectable(min = 1, max = 600))]
+    morph_frames: u32,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    solid: Solid,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    edges: Vec<Edge>,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    colours: Vec<LinSrgba>,
+    /// The solid being morphed into, with its edges and colours.
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    next: Option<(Solid, Vec<Edge>, Vec<LinSrgba>)>,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    shape_ticks: u32,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    from: UnitQuaternion<f32>,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    to: UnitQuaternion<f32>,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    tumble_ticks: u32,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    line_cache: Vec<sdfu::Line<f32, Vector3<f32>>>,
+}
+
+impl Default for Polyhedra {
+    fn default() -> Self {
+        Self::new(Solid::Tetrahedron)
+    }
+}
+
+impl std::fmt::Debug for Polyhedra {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Polyhedra")
+            .field("solid", &self.solid)
+            .finish()
+    }
+}
+
+impl Polyhedra {
+    pub fn new(solid: Solid) -> Self {
+        let edges = solid.edges();
+        let colours = edge_colours(edges.len(), rand::thread_rng().gen_range(0.0..360.0));
+
+        Self {
+            size: 3.5,
+            thickness: 0.1,
+            tumble_frames: 180,
+            hold_frames: 600,
+            morph_frames: 120,
+            solid,
+            edges,
+            colours,
+            next: None,
+            shape_ticks: 0,
+            from: random_rotation().into(),
+            to: random_rotation().into(),
+            tumble_ticks: 0,
+            line_cache: Vec::new(),
+        }
+    }
+
+    pub fn with_size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn with_thickness(mut self, thickness: f32) -> Self {
+        self.thickness = thickness;
+        self
+    }
+
+    pub fn with_timing(mut self, tumble_frames: u32, hold_frames: u32, morph_frames: u32) -> Self {
+        self.tumble_frames = tumble_frames;
+        self.hold_frames = hold_frames;
+        self.morph_frames = morph_frames;
+        self
+    }
+
+    fn rotation(&mut self) -> UnitQuaternion<f32> {
+        self.tumble_ticks += 1;
+
+        if self.tumble_ticks >= self.tumble_frames {
+            self.tumble_ticks = 0;
+            self.from = self.to;
+            self.to = random_rotation().into();
+        }
+
+        let t = smoothstep(self.tumble_ticks as f32 / self.tumble_frames as f32);
+
+        // no way round is shorter when they are half a turn apart, so just
+        // snap
+        self.from.try_slerp(&self.to, t, 1.0e-6).unwrap_or(self.to)
+    }
+
+    /// How far through the current morph, starting one off when the solid
+    /// has been held long enough.
+    fn morph(&mut self) -> f32 {
+        self.shape_ticks += 1;
+
+        if self.next.is_none() && self.shape_ticks >= self.hold_frames {
+            let solid = self.solid.next();
+            let edges = solid.edges();
+            let colours = edge_colours(edges.len(), rand::thread_rng().gen_range(0.0..360.0));
+
+            self.next = Some((solid, edges, colours));
+            self.shape_ticks = 0;
+        }
+
+        if self.next.is_some() && self.shape_ticks >= self.morph_frames {
+            if let Some((solid, edges, colours)) = self.next.take() {
+                self.solid = solid;
+                self.edges = edges;
+                self.colours = colours;
+            }
+            self.shape_ticks = 0;
+        }
+
+        match self.next {
+            Some(_) => smoothstep(self.shape_ticks as f32 / self.morph_frames as f32),
+            None => 0.0,
+        }
+    }
+}
+
+impl Animation for Polyhedra {
+    fn next_frame(&mut self, frame: &mut Frame) {
+        let rotation = self.rotation();
+        let t = self.morph();
+
+        let centre = Vector3::repeat((Frame::LAYERS - 1) as f32 / 2.0);
+        let place = |v: Vector3<f32>| rotation * v * self.size + centre;
+
+        self.line_cache.clear();
+
+        match &self.next {
+            // edges are paired up by index, wrapping round the shorter list,
+            // and each slides from its old place to its new one
+            Some((_, next_edges, next_colours)) => {
+                let count = self.edges.len().max(next_edges.len());
+
+                for i in 0..count {
+                    let (a0, b0) = self.edges[i % self.edges.len()];
+                    let (a1, b1) = next_edges[i % next_edges.len()];
+                    let colour = self.colours[i % self.colours.len()]
+                        .mix(&next_colours[i % next_colours.len()], t);
+
+                    self.line_cache.push(sdfu::Line::new(
+                        place(a0.lerp(&a1, t)),
+                        place(b0.lerp(&b1, t)),
+                        self.thickness,
+                        colour,
+                    ));
+                }
+            }
+            None => {
+                for (&(a, b), &colour) in self.edges.iter().zip(&self.colours) {
+                    self.line_cache.push(sdfu::Line::new(
+                        place(a),
+                        place(b),
+                        self.thickness,
+                        colour,
+                    ));
+                }
+            }
+        }
+
+        let union = MultiUnion::hard(&self.line_cache);
+
+        // thin edges flicker when only sampled at voxel centres
+        render_sdf_with(union, frame, &RenderOptions::antialiased());
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.solid)
+            .with_size(self.size)
+            .with_thickness(self.thickness)
+            .with_timing(self.tumble_frames, self.hold_frames, self.morph_frames);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn platonic_solids() {
+        let counts = [(4, 6), (8, 12), (6, 12), (20, 30), (12, 30)];
+
+        for (solid, (vertices, edges)) in Solid::ALL.into_iter().zip(counts) {
+            let corners = solid.vertices();
+            assert_eq!(corners.len(), vertices, "{:?}", solid);
+            assert!(corners.iter().all(|v| (v.norm() - 1.0).abs() < 1e-5));
+
+            let lines = solid.edges();
+            assert_eq!(lines.len(), edges, "{:?}", solid);
+
+            // every corner meets the same number of edges
+            let degree = 2 * edges / vertices;
+            for corner in &corners {
+                let meeting = lines
+                    .iter()
+                    .filter(|(a, b)| a == corner || b == corner)
+                    .count();
+                assert_eq!(meeting, degree, "{:?}", solid);
+            }
+        }
+    }
+
+    #[test]
+    fn solids_take_turns() {
+        let mut solid = Solid::Tetrahedron;
+        let mut seen = vec![solid];
+
+        for _ in 0..4 {
+            solid = solid.next();
+            seen.push(solid);
+        }
+
+        assert_eq!(seen, Solid::ALL);
+        assert_eq!(solid.next(), Solid::Tetrahedron);
+    }
+
+    #[test]
+    fn holds_then_morphs() {
+        let mut polyhedra = Polyhedra::new(Solid::Cube).with_timing(10, 3, 4);
+
+        let morphs = (0..10).map(|_| polyhedra.morph()).collect::<Vec<_>>();
+
+        assert_eq!(&morphs[..2], [0.0, 0.0]);
+        assert!(morphs[2..6].windows(2).all(|w| w[0] < w[1]), "{:?}", morphs);
+        assert!(morphs[2..6].iter().all(|t| (0.0..1.0).contains(t)));
+        assert_eq!(polyhedra.solid, Solid::Octahedron);
+        assert_eq!(polyhedra.edges.len(), 12);
+    }
+}
@@ -0,0 +1,299 @@
+use nalgebra::{vector, Vector3};
+use palette::{LinSrgba, Mix};
+use rand::Rng;
+
+use crate::{
+    animation::Animation,
+    frame::Frame,
+    particles::{splat, ColourOverLife, Emitter, ParticleSystem},
+};
+
+/// Colours for the head of each streak and the end of its tail.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Theme {
+    Water,
+    /// Green code, as in the film.
+    Matrix,
+    Embers,
+    Custom {
+        head: LinSrgba,
+        tail: LinSrgba,
+    },
+}
+
+impl Theme {
+    fn colours(&self) -> (LinSrgba, LinSrgba) {
+        match *self {
+            Theme::Water => (
+                LinSrgba::new(0.7, 0.8, 1.0, 1.0),
+                LinSrgba::new(0.0, 0.1, 0.8, 1.0),
+            ),
+            Theme::Matrix => (
+                LinSrgba::new(0.8, 1.0, 0.8, 1.0),
+                LinSrgba::new(0.0, 0.6, 0.0, 1.0),
+            ),
+            Theme::Embers => (
+                LinSrgba::new(1.0, 0.9, 0.4, 1.0),
+                LinSrgba::new(0.8, 0.05, 0.0, 1.0),
+            ),
+            Theme::Custom { head, tail } => (head, tail),
+        }
+    }
+}
+
+/// Falls like the drops in `Waves`, with a tail drawn behind it.
+struct Streak {
+    x: u8,
+    z: u8,
+    /// Height of the head.
+    y: f32,
+    speed: f32,
+    length: f32,
+}
+
+/// Streaks of light falling down random columns, splashing when they reach
+/// the bottom.
+#[cfg_attr(feature = "visual", derive(bevy_inspector_egui::Inspectable))]
+pub struct Rain {
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    theme: Theme,
+    /// Streaks started a second, on average.
+    #[cfg_attr(feature = "visual", inspectable(min = 0.0, max = 60.0))]
+    density: f32,
+    /// Voxels a second.
+    #[cfg_attr(feature = "visual", inspectable(min = 0.5, max = 30.0))]
+    min_speed: f32,
+    #[cfg_attr(feature = "visual", inspectable(min = 0.5, max = 30.0))]
+    max_speed: f32,
+    /// Voxels behind the head, varied a little per streak.
+    #[cfg_attr(feature = "visual", inspectable(min = 0.0, max = 8.0))]
+    tail_length: f32,
+    /// Droplets thrown up by each splash, zero turns splashes off.
+    #[cfg_attr(feature = "visual", inspectable(min = 0, max = 30))]
+    splash_size: usize,
+    /// Seconds simulated each frame.
+    #[cfg_attr(feature = "visual", inspectable(min = 0.001, max = 0.1))]
+    time_step: f32,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    streaks: Vec<Streak>,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    splashes: ParticleSystem,
+}
+
+impl Default for Rain {
+    fn default() -> Self {
+        Self {
+            theme: Theme::Water,
+            density: 12.0,
+            min_speed: 6.0,
+            max_speed: 14.0,
+            tail_length: 3.0,
+            splash_size: 6,
+            time_step: 1.0 / 60.0,
+            streaks: Vec::new(),
+            splashes: ParticleSystem::new().with_drag(1.0),
+        }
+    }
+}
+
+impl std::fmt::Debug for Rain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Rain").field("theme", &self.theme).finish()
+    }
+}
+
+impl Rain {
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    pub fn with_density(mut self, density: f32) -> Self {
+        self.density = density;
+        self
+    }
+
+    pub fn with_speed(mut self, min: f32, max: f32) -> Self {
+        self.min_speed = min;
+        self.max_speed = max;
+        self
+    }
+
+    pub fn with_tail_length(mut self, length: f32) -> Self {
+        self.tail_length = length;
+        self
+    }
+
+    pub fn with_splash_size(mut self, droplets: usize) -> Self {
+        self.splash_size = droplets;
+        self
+    }
+
+    fn splash(&mut self, x: u8, z: u8) {
+        let (head, _) = self.theme.colours();
+
+        Emitter::default()
+            .with_position(vector![x as f32, 0.0, z as f32])
+            .with_direction(Vector3::y_axis(), 1.2)
+            .with_speed(1.5..3.5)
+            .with_lifetime(0.2..0.5)
+            .with_colour(ColourOverLife::fading(head))
+            .burst(self.splash_size, &mut self.splashes);
+    }
+}
+
+impl Animation for Rain {
+    fn next_frame(&mut self, frame: &mut Frame) {
+        let mut rng = rand::thread_rng();
+        let dt = self.time_step;
+
+        let expected = (self.density * dt).max(0.0);
+        let count = expected as usize + rng.gen_bool(expected.fract() as f64) as usize;
+
+        for _ in 0..count {
+            let speed = if self.min_speed < self.max_speed {
+                rng.gen_range(self.min_speed..self.max_speed)
+            } else {
+                self.min_speed
+            };
+
+            self.streaks.push(Streak {
+                x: rng.gen_range(0..8),
+                z: rng.gen_range(0..8),
+                y: 8.0,
+                speed,
+                length: self.tail_length * rng.gen_range(0.7..1.3),
+            });
+        }
+
+        let mut landed = Vec::new();
+
+        // streaks keep falling until the end of the tail is out of sight
+        self.streaks.retain_mut(|streak| {
+            let was_above = streak.y >= 0.0;
+            streak.y -= streak.speed * dt;
+
+            if was_above && streak.y < 0.0 {
+                landed.push((streak.x, streak.z));
+            }
+
+            streak.y + streak.length >= 0.0
+        });
+
+        for (x, z) in landed {
+            self.splash(x, z);
+        }
+
+        self.splashes.step(dt);
+
+        frame.zero();
+
+        let (head, tail) = self.theme.colours();
+
+        for streak in &self.streaks {
+            let cells = streak.length.ceil() as usize;
+
+            for i in 0..=cells {
+                let t = if cells == 0 {
+                    0.0
+                } else {
+                    i as f32 / cells as f32
+                };
+                let height = streak.y + streak.length * t;
+
+                // the head is lost in the splash once it lands
+                if height < 0.0 {
+                    continue;
+                }
+
+                let mut colour = head.mix(&tail, t);
+                colour.alpha *= 1.0 - t;
+
+                splat(
+                    frame,
+                    vector![streak.x as f32, height, streak.z as f32],
+                    colour,
+                );
+            }
+        }
+
+        self.splashes.render(frame);
+    }
+
+    fn reset(&mut self) {
+        self.streaks.clear();
+        self.splashes.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One streak at the top of the column at (2, 5), falling a voxel a
+    /// frame.
+    fn one_streak(splash_size: usize) -> Rain {
+        let mut rain = Rain::default()
+            .with_density(0.0)
+            .with_splash_size(splash_size);
+        rain.streaks.push(Streak {
+            x: 2,
+            z: 5,
+            y: 8.0,
+            speed: 60.0,
+            length: 2.0,
+        });
+        rain
+    }
+
+    #[test]
+    fn streaks_splash_once_when_they_land() {
+        let mut rain = one_streak(4);
+        let mut frame = Frame::new();
+
+        for _ in 0..8 {
+            rain.next_frame(&mut frame);
+            assert!(rain.splashes.is_empty());
+        }
+
+        // landing, then the tail falls out of sight
+        rain.next_frame(&mut frame);
+        assert_eq!(rain.splashes.len(), 4);
+        assert_eq!(rain.streaks.len(), 1);
+
+        rain.next_frame(&mut frame);
+        rain.next_frame(&mut frame);
+        assert!(rain.streaks.is_empty());
+        assert_eq!(rain.splashes.len(), 4);
+    }
+
+    #[test]
+    fn streaks_fall_down_their_column() {
+        let mut rain = one_streak(0);
+        let mut frame = Frame::new();
+
+        for _ in 0..12 {
+            rain.next_frame(&mut frame);
+
+            let off_column = frame
+                .pixels()
+                .filter(|&(x, _, z, c)| c.alpha > 0.0 && (x, z) != (2, 5))
+                .count();
+            assert_eq!(off_column, 0);
+        }
+        assert!(rain.splashes.is_empty());
+    }
+
+    #[test]
+    fn no_rain() {
+        let mut frame = Frame::new();
+
+        for density in [0.0, -5.0] {
+            let mut rain = Rain::default().with_density(density);
+            for _ in 0..100 {
+                rain.next_frame(&mut frame);
+            }
+            assert!(rain.streaks.is_empty());
+        }
+    }
+}
@@ -0,0 +1,486 @@
+use nalgebra::{vector, Vector3};
+use palette::LinSrgba;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use crate::{
+    animation::{Animation, TerminatingAnimation},
+    frame::Frame,
+};
+
+const SIZE: usize = 8;
+const CELLS: usize = SIZE * SIZE * SIZE;
+
+fn idx(x: usize, y: usize, z: usize) -> usize {
+    x + y * SIZE + z * SIZE * SIZE
+}
+
+fn coords(i: usize) -> [usize; 3] {
+    [i % SIZE, (i / SIZE) % SIZE, i / (SIZE * SIZE)]
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Material {
+    Empty,
+    /// Falls straight or diagonally, so it piles up.
+    Sand,
+    /// Falls like sand but also flows sideways, so it levels out.
+    Water,
+    /// Never moves.
+    Wall,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Cell {
+    material: Material,
+    /// Brightness, varied a little per grain so piles have texture.
+    shade: f32,
+}
+
+impl Cell {
+    const EMPTY: Cell = Cell {
+        material: Material::Empty,
+        shade: 0.0,
+    };
+
+    fn colour(&self) -> LinSrgba {
+        let s = self.shade;
+
+        match self.material {
+            Material::Empty => LinSrgba::new(0.0, 0.0, 0.0, 0.0),
+            Material::Sand => LinSrgba::new(0.9 * s, 0.6 * s, 0.15 * s, 1.0),
+            Material::Water => LinSrgba::new(0.0, 0.25 * s, 1.0 * s, 0.7),
+            Material::Wall => LinSrgba::new(0.3, 0.3, 0.3, 1.0),
+        }
+    }
+}
+
+/// The 26 neighbour offsets, grouped into the ways a grain can move under
+/// the current gravity.
+struct Moves {
+    /// The neighbours most nearly straight down.
+    fall: Vec<[isize; 3]>,
+    /// Other neighbours below, for sliding down the side of a pile.
+    slide: Vec<[isize; 3]>,
+    /// Neighbours level with the grain, for water to flow into.
+    flow: Vec<[isize; 3]>,
+}
+
+impl Moves {
+    fn new(gravity: Vector3<f32>) -> Self {
+        let mut offsets = Vec::with_capacity(26);
+
+        for dz in -1..=1 {
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    if (dx, dy, dz) != (0, 0, 0) {
+                        let dir = vector![dx as f32, dy as f32, dz as f32].normalize();
+                        offsets.push(([dx, dy, dz], dir.dot(&gravity)));
+                    }
+                }
+            }
+        }
+
+        let best = offsets.iter().map(|&(_, d)| d).fold(f32::MIN, f32::max);
+        let within = |lo: f32, hi: f32| {
+            offsets
+                .iter()
+                .filter(|&&(_, d)| d >= lo && d < hi)
+                .map(|&(o, _)| o)
+                .collect::<Vec<_>>()
+        };
+
+        Self {
+            fall: within(best - 0.05, f32::MAX),
+            slide: within(0.4, best - 0.05),
+            flow: within(-0.2, 0.4),
+        }
+    }
+}
+
+/// Falling sand and water poured in from the top, piling up around walls
+/// while the cube slowly tips from side to side. The same seed always plays
+/// out the same way, and it ends once the cube is full.
+#[cfg_attr(feature = "visual", derive(bevy_inspector_egui::Inspectable))]
+pub struct Sand {
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    seed: u64,
+    /// Grains dropped each step, on average.
+    #[cfg_attr(feature = "visual", inspectable(min = 0.0, max = 8.0))]
+    pour_rate: f32,
+    /// Fraction of grains which are water rather than sand.
+    #[cfg_attr(feature = "visual", inspectable(min = 0.0, max = 1.0))]
+    water: f32,
+    /// Wall shelves placed on reset.
+    #[cfg_attr(feature = "visual", inspectable(min = 0, max = 8))]
+    shelves: usize,
+    /// How far from straight down gravity swings, zero keeps it still.
+    #[cfg_attr(feature = "visual", inspectable(min = 0.0, max = 2.0))]
+    tilt: f32,
+    /// Radians of tipping each step.
+    #[cfg_attr(feature = "visual", inspectable(min = 0.0, max = 0.1))]
+    tilt_speed: f32,
+    #[cfg_attr(feature = "visual", inspectable(min = 1, max = 30))]
+    frames_per_step: u32,
+    #[cfg_attr(feature = "visual", inspectable(read_only))]
+    filled: usize,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    cells: Vec<Cell>,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    rng: StdRng,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    steps: u32,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    ticks: u32,
+    /// Steps in a row with nowhere left to pour.
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    blocked: u32,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    finished: bool,
+}
+
+/// Steps the top has to stay full for, in case the cube tipping opens up
+/// room again.
+const FULL_STEPS: u32 = 30;
+
+impl Default for Sand {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl std::fmt::Debug for Sand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sand")
+            .field("seed", &self.seed)
+            .field("filled", &self.filled)
+            .finish()
+    }
+}
+
+impl Sand {
+    pub fn new(seed: u64) -> Self {
+        let mut sand = Self {
+            seed,
+            pour_rate: 0.5,
+            water: 0.3,
+            shelves: 3,
+            tilt: 0.8,
+            tilt_speed: 0.02,
+            frames_per_step: 3,
+            filled: 0,
+            cells: vec![Cell::EMPTY; CELLS],
+            rng: StdRng::seed_from_u64(seed),
+            steps: 0,
+            ticks: 0,
+            blocked: 0,
+            finished: false,
+        };
+
+        sand.restart();
+        sand
+    }
+
+    pub fn with_pour_rate(mut self, grains: f32) -> Self {
+        self.pour_rate = grains;
+        self
+    }
+
+    pub fn with_water(mut self, fraction: f32) -> Self {
+        self.water = fraction;
+        self
+    }
+
+    pub fn with_shelves(mut self, shelves: usize) -> Self {
+        self.shelves = shelves;
+        self.restart();
+        self
+    }
+
+    pub fn with_tilt(mut self, tilt: f32, speed: f32) -> Self {
+        self.tilt = tilt;
+        self.tilt_speed = speed;
+        self
+    }
+
+    pub fn with_frames_per_step(mut self, frames: u32) -> Self {
+        self.frames_per_step = frames;
+        self
+    }
+
+    fn restart(&mut self) {
+        self.rng = StdRng::seed_from_u64(self.seed);
+        self.cells.fill(Cell::EMPTY);
+
+        // 3x3 slabs part way up, a gap left under each to fill
+        for _ in 0..self.shelves {
+            let (x, y, z) = (
+                self.rng.gen_range(0..SIZE - 2),
+                self.rng.gen_range(2..SIZE - 2),
+                self.rng.gen_range(0..SIZE - 2),
+            );
+
+            for dz in 0..3 {
+                for dx in 0..3 {
+                    self.cells[idx(x + dx, y, z + dz)] = Cell {
+                        material: Material::Wall,
+                        shade: 1.0,
+                    };
+                }
+            }
+        }
+
+        self.filled = self.count_filled();
+        self.steps = 0;
+        self.ticks = 0;
+        self.blocked = 0;
+        self.finished = false;
+    }
+
+    fn count_filled(&self) -> usize {
+        self.cells
+            .iter()
+            .filter(|c| c.material != Material::Empty)
+            .count()
+    }
+
+    /// Mostly down, swinging around on two axes at different rates so it
+    /// doesn't settle into a repeating rock.
+    fn gravity(&self) -> Vector3<f32> {
+        let t = self.steps as f32 * self.tilt_speed;
+
+        vector![self.tilt * t.sin(), -1.0, self.tilt * (t * 0.63).sin()].normalize()
+    }
+
+    fn neighbour(i: usize, [dx, dy, dz]: [isize; 3]) -> Option<usize> {
+        let [x, y, z] = coords(i);
+        let moved = [(x, dx), (y, dy), (z, dz)]
+            .map(|(c, d)| usize::try_from(c as isize + d).ok().filter(|&c| c < SIZE));
+
+        match moved {
+            [Some(x), Some(y), Some(z)] => Some(idx(x, y, z)),
+            _ => None,
+        }
+    }
+
+    /// Where the grain at `i` can go out of `options`, water giving way to
+    /// sand so sand sinks.
+    fn pick(&mut self, i: usize, options: &[[isize; 3]]) -> Option<usize> {
+        let material = self.cells[i].material;
+
+        let free = options
+            .iter()
+            .filter_map(|&o| Self::neighbour(i, o))
+            .filter(|&j| match self.cells[j].material {
+                Material::Empty => true,
+                Material::Water => material == Material::Sand,
+                _ => false,
+            })
+            .collect::<Vec<_>>();
+
+        free.choose(&mut self.rng).copied()
+    }
+
+    fn pour(&mut self) {
+        let expected = self.pour_rate.max(0.0);
+        let count = expected as usize + self.rng.gen_bool(expected.fract() as f64) as usize;
+
+        let mut open = (0..SIZE * SIZE)
+            .map(|n| idx(n % SIZE, SIZE - 1, n / SIZE))
+            .filter(|&i| self.cells[i].material == Material::Empty)
+            .collect::<Vec<_>>();
+
+        if open.is_empty() {
+            self.blocked += 1;
+            return;
+        }
+        self.blocked = 0;
+
+        open.shuffle(&mut self.rng);
+
+        for &i in open.iter().take(count) {
+            let material = if self.rng.gen_bool(self.water.clamp(0.0, 1.0) as f64) {
+                Material::Water
+            } else {
+                Material::Sand
+            };
+
+            self.cells[i] = Cell {
+                material,
+                shade: self.rng.gen_range(0.7..1.0),
+            };
+        }
+    }
+
+    fn step(&mut self) {
+        let gravity = self.gravity();
+        let moves = Moves::new(gravity);
+
+        // grains furthest along gravity move first, to make room for the
+        // ones behind them
+        let mut order = (0..CELLS).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| {
+            let height = |i| {
+                let [x, y, z] = coords(i);
+                vector![x as f32, y as f32, z as f32].dot(&gravity)
+            };
+            height(b).total_cmp(&height(a))
+        });
+
+        let mut moved = vec![false; CELLS];
+
+        for i in order {
+            let material = self.cells[i].material;
+            if moved[i] || matches!(material, Material::Empty | Material::Wall) {
+                continue;
+            }
+
+            let target = self
+                .pick(i, &moves.fall)
+                .or_else(|| self.pick(i, &moves.slide))
+                .or_else(|| match material {
+                    Material::Water => self.pick(i, &moves.flow),
+                    _ => None,
+                });
+
+            if let Some(j) = target {
+                self.cells.swap(i, j);
+                moved[i] = true;
+                moved[j] = true;
+            }
+        }
+
+        self.pour();
+        self.steps += 1;
+        self.filled = self.count_filled();
+
+        if self.filled == CELLS || self.blocked >= FULL_STEPS {
+            dbg!("sand has filled the cube", self.steps, self.filled);
+            self.finished = true;
+        }
+    }
+}
+
+impl Animation for Sand {
+    fn next_frame(&mut self, frame: &mut Frame) {
+        self.ticks += 1;
+
+        if self.ticks >= self.frames_per_step {
+            self.ticks = 0;
+
+            if !self.finished {
+                self.step();
+            }
+        }
+
+        for (x, y, z, pix) in frame.pixels_mut() {
+            *pix = self.cells[idx(x as usize, y as usize, z as usize)].colour();
+        }
+    }
+
+    fn reset(&mut self) {
+        self.restart();
+    }
+
+    fn ended(&self) -> bool {
+        self.finished
+    }
+}
+
+impl TerminatingAnimation for Sand {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grains(sand: &Sand) -> usize {
+        sand.cells
+            .iter()
+            .filter(|c| matches!(c.material, Material::Sand | Material::Water))
+            .count()
+    }
+
+    #[test]
+    fn negative_pour_rates_pour_nothing() {
+        let mut sand = Sand::new(1).with_pour_rate(-1.5).with_frames_per_step(1);
+        let mut frame = Frame::new();
+
+        for _ in 0..20 {
+            sand.next_frame(&mut frame);
+        }
+        assert_eq!(grains(&sand), 0);
+    }
+
+    fn materials(sand: &Sand) -> Vec<Material> {
+        sand.cells.iter().map(|c| c.material).collect()
+    }
+
+    fn run(sand: &mut Sand, steps: usize) {
+        for _ in 0..steps {
+            sand.step();
+        }
+    }
+
+    #[test]
+    fn the_same_seed_plays_out_the_same() {
+        let new = |seed| Sand::new(seed).with_shelves(3).with_water(0.4);
+        let (mut a, mut b, mut c) = (new(7), new(7), new(8));
+
+        run(&mut a, 60);
+        run(&mut b, 60);
+        run(&mut c, 60);
+        assert_eq!(materials(&a), materials(&b));
+        assert_ne!(materials(&a), materials(&c));
+
+        // and again after a reset
+        a.reset();
+        run(&mut a, 60);
+        assert_eq!(materials(&a), materials(&b));
+    }
+
+    #[test]
+    fn grains_fall_and_walls_stay() {
+        let mut sand = Sand::new(1)
+            .with_shelves(2)
+            .with_pour_rate(0.0)
+            .with_tilt(0.0, 0.0);
+        let walls = materials(&sand);
+
+        let top = idx(3, SIZE - 1, 4);
+        sand.cells[top] = Cell {
+            material: Material::Sand,
+            shade: 1.0,
+        };
+        run(&mut sand, SIZE);
+
+        assert_eq!(grains(&sand), 1);
+        let grain = sand
+            .cells
+            .iter()
+            .position(|c| c.material == Material::Sand)
+            .unwrap();
+        assert_ne!(grain, top);
+        assert!(Sand::neighbour(grain, [0, -1, 0])
+            .is_none_or(|below| sand.cells[below].material == Material::Wall));
+
+        let still_walls = materials(&sand)
+            .iter()
+            .zip(&walls)
+            .all(|(now, was)| (*now == Material::Wall) == (*was == Material::Wall));
+        assert!(still_walls);
+    }
+
+    #[test]
+    fn ends_once_full() {
+        let mut sand = Sand::new(3).with_pour_rate(8.0);
+
+        for _ in 0..2000 {
+            if sand.ended() {
+                break;
+            }
+            sand.step();
+        }
+
+        assert!(sand.ended());
+        assert!(sand.filled > CELLS * 3 / 4, "{}", sand.filled);
+    }
+}
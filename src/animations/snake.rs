@@ -0,0 +1,469 @@
+use std::collections::VecDeque;
+
+use palette::{LinSrgba, Mix};
+use rand::{seq::IteratorRandom, Rng};
+
+use crate::{
+    animation::{Animation, TerminatingAnimation},
+    frame::Frame,
+};
+
+type Cell = [i8; 3];
+
+const SIZE: i8 = 8;
+const CELLS: usize = 512;
+
+/// Frames the death or victory fade lasts.
+const ENDING_FRAMES: u32 = 90;
+
+const DIRECTIONS: [Cell; 6] = [
+    [1, 0, 0],
+    [-1, 0, 0],
+    [0, 1, 0],
+    [0, -1, 0],
+    [0, 0, 1],
+    [0, 0, -1],
+];
+
+fn idx([x, y, z]: Cell) -> usize {
+    x as usize + 8 * (y as usize + 8 * z as usize)
+}
+
+fn neighbours(cell: Cell) -> impl Iterator<Item = Cell> {
+    DIRECTIONS.into_iter().filter_map(move |d| {
+        let next = [0, 1, 2].map(|i| cell[i] + d[i]);
+        next.iter().all(|c| (0..SIZE).contains(c)).then_some(next)
+    })
+}
+
+/// Cells the head can't move into. The tail is free unless the snake is
+/// growing, as it moves on at the same time.
+fn blocked(body: &VecDeque<Cell>, growing: bool) -> Vec<bool> {
+    let mut blocked = vec![false; CELLS];
+
+    let tail_moves = !growing && body.len() > 2;
+    for &cell in body.iter().take(body.len() - tail_moves as usize) {
+        blocked[idx(cell)] = true;
+    }
+
+    blocked
+}
+
+/// The body, apart from the tail which the head is heading for.
+fn blocked_tail_free(body: &VecDeque<Cell>) -> Vec<bool> {
+    let mut blocked = blocked(body, true);
+    blocked[idx(body[body.len() - 1])] = false;
+    blocked
+}
+
+/// Breadth first search out from `from`, giving where each reachable cell
+/// was reached from.
+fn search(from: Cell, blocked: &[bool]) -> Vec<Option<Cell>> {
+    let mut parents = vec![None; CELLS];
+    let mut queue = VecDeque::from([from]);
+
+    while let Some(cell) = queue.pop_front() {
+        for n in neighbours(cell) {
+            if !blocked[idx(n)] && parents[idx(n)].is_none() && n != from {
+                parents[idx(n)] = Some(cell);
+                queue.push_back(n);
+            }
+        }
+    }
+
+    parents
+}
+
+/// The shortest path found by `search` to `to`, not including the start.
+fn path(parents: &[Option<Cell>], to: Cell) -> Option<Vec<Cell>> {
+    let mut path = vec![to];
+
+    while let Some(parent) = parents[idx(*path.last().unwrap())] {
+        path.push(parent);
+    }
+
+    // the walk back ends at the start, unless `to` was never reached
+    if path.len() < 2 {
+        return None;
+    }
+
+    path.pop();
+    path.reverse();
+    Some(path)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Phase {
+    Playing,
+    Ending { frames: u32, died: bool },
+    Over,
+}
+
+/// A snake which finds its way to each piece of food around its own body,
+/// growing as it eats. It ends when it gets stuck, flashing red as it fades,
+/// or once it fills `fill_target` of the cube.
+#[cfg_attr(feature = "visual", derive(bevy_inspector_egui::Inspectable))]
+pub struct Snake {
+    #[cfg_attr(feature = "visual", inspectable(min = 1, max = 30))]
+    frames_per_move: u32,
+    /// Segments added for each piece of food.
+    #[cfg_attr(feature = "visual", inspectable(min = 1, max = 8))]
+    growth: usize,
+    /// Fraction of the cube the snake has to fill to win.
+    #[cfg_attr(feature = "visual", inspectable(min = 0.0, max = 1.0))]
+    fill_target: f32,
+    #[cfg_attr(feature = "visual", inspectable(read_only))]
+    length: usize,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    head_colour: LinSrgba,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    tail_colour: LinSrgba,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    food_colour: LinSrgba,
+    /// Head first.
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    body: VecDeque<Cell>,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    food: Option<Cell>,
+    /// Segments still to grow.
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    pending: usize,
+    /// Moves since the last meal.
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    hunger: usize,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    ticks: u32,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    phase: Phase,
+}
+
+impl Default for Snake {
+    fn default() -> Self {
+        let mut snake = Self {
+            frames_per_move: 6,
+            growth: 2,
+            fill_target: 0.5,
+            length: 0,
+            head_colour: LinSrgba::new(0.1, 1.0, 0.1, 1.0),
+            tail_colour: LinSrgba::new(0.0, 0.1, 0.6, 1.0),
+            food_colour: LinSrgba::new(1.0, 0.3, 0.0, 1.0),
+            body: VecDeque::new(),
+            food: None,
+            pending: 0,
+            hunger: 0,
+            ticks: 0,
+            phase: Phase::Playing,
+        };
+
+        snake.restart();
+        snake
+    }
+}
+
+impl Snake {
+    pub fn with_frames_per_move(mut self, frames: u32) -> Self {
+        self.frames_per_move = frames;
+        self
+    }
+
+    pub fn with_growth(mut self, growth: usize) -> Self {
+        self.growth = growth;
+        self
+    }
+
+    pub fn with_fill_target(mut self, fraction: f32) -> Self {
+        self.fill_target = fraction;
+        self
+    }
+
+    /// The body fades from `head` to `tail` along its length.
+    pub fn with_colours(mut self, head: LinSrgba, tail: LinSrgba, food: LinSrgba) -> Self {
+        self.head_colour = head;
+        self.tail_colour = tail;
+        self.food_colour = food;
+        self
+    }
+
+    fn restart(&mut self) {
+        let mut rng = rand::thread_rng();
+        let start = [(); 3].map(|_| rng.gen_range(0..SIZE));
+
+        self.body = VecDeque::from([start]);
+        self.length = 1;
+        self.pending = self.growth;
+        self.food = None;
+        self.hunger = 0;
+        self.ticks = 0;
+        self.phase = Phase::Playing;
+        self.place_food();
+    }
+
+    fn place_food(&mut self) {
+        let mut occupied = vec![false; CELLS];
+        for &cell in &self.body {
+            occupied[idx(cell)] = true;
+        }
+
+        self.food = (0..CELLS)
+            .filter(|&i| !occupied[i])
+            .choose(&mut rand::thread_rng())
+            .map(|i| [i % 8, (i / 8) % 8, i / 64].map(|c| c as i8));
+    }
+
+    /// Head for the food if the snake could still reach its tail once it
+    /// got there. Otherwise wander, keeping the tail in reach but as far from
+    /// it as possible so there's room to follow it around, and failing that
+    /// stall towards whichever side has the most room.
+    fn choose_step(&self) -> Option<Cell> {
+        let head = self.body[0];
+        let blocked = blocked(&self.body, self.pending > 0);
+
+        if let Some(path) = self
+            .food
+            .and_then(|food| path(&search(head, &blocked), food))
+        {
+            if self.tail_reachable(&path) {
+                return Some(path[0]);
+            }
+        }
+
+        let free = || neighbours(head).filter(|&n| !blocked[idx(n)]);
+
+        let wander = free()
+            .filter_map(|n| {
+                let (body, _) = self.simulate(&[n]);
+                let tail = body[body.len() - 1];
+                let to_tail = path(&search(n, &blocked_tail_free(&body)), tail)?;
+
+                Some((n, to_tail.len()))
+            })
+            .max_by_key(|&(_, dist)| dist);
+
+        if let Some((step, _)) = wander {
+            return Some(step);
+        }
+
+        free().max_by_key(|&n| {
+            let mut blocked = blocked.clone();
+            blocked[idx(n)] = true;
+            search(n, &blocked).iter().flatten().count()
+        })
+    }
+
+    /// The body and growth still to come after moving along `path`.
+    fn simulate(&self, path: &[Cell]) -> (VecDeque<Cell>, usize) {
+        let mut body = self.body.clone();
+        let mut pending = self.pending;
+
+        for &step in path {
+            body.push_front(step);
+
+            if Some(step) == self.food {
+                pending += self.growth;
+            }
+
+            if pending > 0 {
+                pending -= 1;
+            } else {
+                body.pop_back();
+            }
+        }
+
+        (body, pending)
+    }
+
+    /// Whether the snake could still reach its tail after following `path`,
+    /// with room to grow while the tail waits.
+    fn tail_reachable(&self, path: &[Cell]) -> bool {
+        let (body, pending) = self.simulate(path);
+        let tail = body[body.len() - 1];
+        let parents = search(body[0], &blocked_tail_free(&body));
+
+        parents[idx(tail)].is_some() && parents.iter().flatten().count() > pending
+    }
+
+    fn advance(&mut self) {
+        // chasing its tail forever without ever being able to eat counts as
+        // stuck too
+        let step = match self.choose_step().filter(|_| self.hunger < CELLS * 2) {
+            Some(step) => step,
+            None => {
+                dbg!("snake is stuck", self.length);
+                self.phase = Phase::Ending {
+                    frames: 0,
+                    died: true,
+                };
+                return;
+            }
+        };
+
+        self.body.push_front(step);
+
+        if Some(step) == self.food {
+            self.pending += self.growth;
+            self.food = None;
+            self.hunger = 0;
+        } else {
+            self.hunger += 1;
+        }
+
+        if self.pending > 0 {
+            self.pending -= 1;
+        } else {
+            self.body.pop_back();
+        }
+
+        self.length = self.body.len();
+
+        if self.food.is_none() {
+            self.place_food();
+        }
+
+        if self.food.is_none() || self.length as f32 >= self.fill_target * CELLS as f32 {
+            dbg!("snake has filled the cube", self.length);
+            self.phase = Phase::Ending {
+                frames: 0,
+                died: false,
+            };
+        }
+    }
+
+    fn segment_colour(&self, i: usize) -> LinSrgba {
+        let t = i as f32 / (self.body.len() - 1).max(1) as f32;
+        self.head_colour.mix(&self.tail_colour, t)
+    }
+}
+
+impl std::fmt::Debug for Snake {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Snake").finish()
+    }
+}
+
+impl Animation for Snake {
+    fn next_frame(&mut self, frame: &mut Frame) {
+        match &mut self.phase {
+            Phase::Playing => {
+                self.ticks += 1;
+
+                if self.ticks >= self.frames_per_move {
+                    self.ticks = 0;
+                    self.advance();
+                }
+            }
+            Phase::Ending { frames, .. } => {
+                *frames += 1;
+
+                if *frames >= ENDING_FRAMES {
+                    self.phase = Phase::Over;
+                }
+            }
+            Phase::Over => {}
+        }
+
+        frame.zero();
+
+        let (fade, died) = match self.phase {
+            Phase::Playing => (0.0, false),
+            Phase::Ending { frames, died } => (frames as f32 / ENDING_FRAMES as f32, died),
+            Phase::Over => return,
+        };
+
+        for (i, &[x, y, z]) in self.body.iter().enumerate() {
+            let mut colour = self.segment_colour(i);
+
+            // a dead snake flashes red a few times as it fades
+            if died && ((fade * 6.0) as u32).is_multiple_of(2) {
+                colour = LinSrgba::new(1.0, 0.0, 0.0, colour.alpha);
+            }
+
+            colour.alpha *= 1.0 - fade;
+            frame.set(x as usize, y as usize, z as usize, colour);
+        }
+
+        if let (Some([x, y, z]), Phase::Playing) = (self.food, self.phase) {
+            frame.set(x as usize, y as usize, z as usize, self.food_colour);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.restart();
+    }
+
+    fn ended(&self) -> bool {
+        self.phase == Phase::Over
+    }
+}
+
+impl TerminatingAnimation for Snake {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn adjacent(a: Cell, b: Cell) -> bool {
+        (0..3).map(|i| (a[i] - b[i]).abs()).sum::<i8>() == 1
+    }
+
+    #[test]
+    fn neighbours_stay_inside() {
+        assert_eq!(neighbours([0, 0, 0]).count(), 3);
+        assert_eq!(neighbours([7, 3, 0]).count(), 4);
+        assert_eq!(neighbours([3, 4, 5]).count(), 6);
+    }
+
+    #[test]
+    fn shortest_paths() {
+        let open = vec![false; CELLS];
+        let parents = search([0, 0, 0], &open);
+
+        let route = path(&parents, [2, 1, 3]).unwrap();
+        assert_eq!(route.len(), 6);
+        assert_eq!(route.last(), Some(&[2, 1, 3]));
+        assert!(adjacent([0, 0, 0], route[0]));
+        assert!(route.windows(2).all(|w| adjacent(w[0], w[1])));
+
+        assert_eq!(path(&parents, [0, 0, 0]), None);
+
+        // walled into the corner
+        let mut walled = open;
+        for cell in neighbours([0, 0, 0]) {
+            walled[idx(cell)] = true;
+        }
+        assert_eq!(path(&search([0, 0, 0], &walled), [2, 1, 3]), None);
+    }
+
+    #[test]
+    fn the_tail_moves_out_of_the_way() {
+        let body = VecDeque::from([[2, 0, 0], [1, 0, 0], [0, 0, 0]]);
+
+        assert!(!blocked(&body, false)[idx([0, 0, 0])]);
+        assert!(blocked(&body, true)[idx([0, 0, 0])]);
+        assert!(blocked(&body, false)[idx([1, 0, 0])]);
+    }
+
+    #[test]
+    fn never_crosses_itself() {
+        let mut snake = Snake::default().with_fill_target(0.25);
+
+        for _ in 0..5000 {
+            if snake.phase != Phase::Playing {
+                break;
+            }
+
+            let head = snake.body[0];
+            snake.advance();
+
+            if snake.phase == Phase::Playing {
+                assert!(adjacent(head, snake.body[0]));
+            }
+
+            let mut seen = vec![false; CELLS];
+            for &cell in &snake.body {
+                assert!(!std::mem::replace(&mut seen[idx(cell)], true), "{:?}", cell);
+            }
+        }
+
+        assert_ne!(snake.phase, Phase::Playing);
+    }
+}
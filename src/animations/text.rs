@@ -0,0 +1,98 @@
+use crate::{
+    animation::{Animation, TerminatingAnimation},
+    font::{draw_text, Surface, Text, TextStyle},
+    frame::Frame,
+};
+
+/// Scrolls a message across the cube once, from off the right hand edge
+/// until it has left the left hand edge.
+#[cfg_attr(feature = "visual", derive(bevy_inspector_egui::Inspectable))]
+pub struct TextScroller {
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    message: String,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    text: Text,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    surface: Surface,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    style: TextStyle,
+    /// Columns moved per frame.
+    #[cfg_attr(feature = "visual", inspectable(min = 0.01, max = 2.0))]
+    speed: f32,
+    #[cfg_attr(feature = "visual", inspectable(read_only))]
+    position: f32,
+}
+
+impl TextScroller {
+    pub fn new(message: impl Into<String>) -> Self {
+        let message = message.into();
+        let text = Text::new(&message);
+        let surface = Surface::Ring;
+
+        Self {
+            message,
+            text,
+            surface,
+            style: TextStyle::default(),
+            speed: 0.25,
+            position: -(surface.width() as f32),
+        }
+    }
+
+    pub fn on(mut self, surface: Surface) -> Self {
+        self.surface = surface;
+        self.position = -(surface.width() as f32);
+        self
+    }
+
+    pub fn with_style(mut self, style: TextStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Change the message without restarting the scroll, for things like
+    /// scores and clocks.
+    pub fn set_message(&mut self, message: impl Into<String>) {
+        self.message = message.into();
+        self.text = Text::new(&self.message);
+    }
+}
+
+impl std::fmt::Debug for TextScroller {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TextScroller")
+            .field("message", &self.message)
+            .finish()
+    }
+}
+
+impl Animation for TextScroller {
+    fn next_frame(&mut self, frame: &mut Frame) {
+        frame.zero();
+
+        draw_text(
+            frame,
+            &self.text,
+            self.position.floor() as isize,
+            self.surface,
+            &self.style,
+        );
+
+        self.position += self.speed;
+    }
+
+    fn reset(&mut self) {
+        self.position = -(self.surface.width() as f32);
+    }
+
+    fn ended(&self) -> bool {
+        self.position >= self.text.width() as f32
+    }
+}
+
+impl TerminatingAnimation for TextScroller {}
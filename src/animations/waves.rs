@@ -1,26 +1,76 @@
 use crate::{
     animation::Animation,
     frame::Frame,
-    sdf::{render_sdf, MultiUnion},
+    sdf::{render_sdf, Bounded, MultiUnion},
 };
+use cube_core::colour_map::ColourMap;
 use itertools::Itertools;
 use nalgebra::{vector, SMatrix, Vector3};
 use palette::{LinSrgba, Mix};
 use rand::Rng;
-use sdfu::SDF;
+
+const SIZE: usize = 8;
+
+fn idx(x: usize, y: usize, z: usize) -> usize {
+    x + y * SIZE + z * SIZE * SIZE
+}
+
+/// What is being simulated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// Drops falling into a pool, its surface drawn as stacked LEDs.
+    Water,
+    /// The wave equation through the whole cube, ripples spreading out from
+    /// each impulse.
+    Wave,
+    /// The heat equation through the whole cube, each impulse a hot spot
+    /// which spreads out and fades.
+    Heat,
+}
+
+/// What happens at the sides of the cube in the volumetric modes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Boundary {
+    /// Waves bounce back and heat stays in.
+    Reflecting,
+    /// Waves are soaked up and heat drains away.
+    Absorbing,
+    /// Opposite sides are joined, so waves leaving one come back in the
+    /// other.
+    Periodic,
+}
 
 #[cfg_attr(feature = "visual", derive(bevy_inspector_egui::Inspectable))]
 pub struct Waves {
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    mode: Mode,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    boundary: Boundary,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    colours: ColourMap,
     c2: f32,
     h2: f32,
     drop_speed: f32,
     delta_t: f32,
     drop_volume: f32,
     drain_rate: f32,
+    #[cfg_attr(feature = "visual", inspectable(min = 0.01, max = 4.0))]
+    drop_blend: f32,
     #[cfg_attr(feature = "visual", inspectable(min = 0.0, max = 100.0))]
     max_gradient: f32,
     #[cfg_attr(feature = "visual", inspectable(read_only))]
     current_volume: f32,
+    /// Chance of an impulse each frame, volumetric modes only.
+    #[cfg_attr(feature = "visual", inspectable(min = 0.0, max = 1.0))]
+    impulse_rate: f32,
+    #[cfg_attr(feature = "visual", inspectable(min = 0.0, max = 10.0))]
+    impulse_strength: f32,
+    /// Amplitude shown at the ends of the colour map.
+    #[cfg_attr(feature = "visual", inspectable(min = 0.01, max = 10.0))]
+    amplitude: f32,
+    /// Wave velocity or heat kept each step.
+    #[cfg_attr(feature = "visual", inspectable(min = 0.5, max = 1.0))]
+    damping: f32,
     #[cfg_attr(feature = "visual", inspectable(ignore))]
     u: SMatrix<f32, 8, 8>,
     #[cfg_attr(feature = "visual", inspectable(ignore))]
@@ -29,37 +79,88 @@ pub struct Waves {
     v: SMatrix<f32, 8, 8>,
     #[cfg_attr(feature = "visual", inspectable(ignore))]
     drops: Vec<(u8, u8, f32)>,
+    /// Amplitude at each voxel, volumetric modes only.
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    field: Vec<f32>,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    field_new: Vec<f32>,
+    #[cfg_attr(feature = "visual", inspectable(ignore))]
+    field_v: Vec<f32>,
     #[cfg_attr(feature = "visual", inspectable(ignore))]
-    sdf_cache: Vec<sdfu::mods::Translate<Vector3<f32>, sdfu::Sphere<f32>>>,
+    sdf_cache: Vec<Bounded<sdfu::mods::Translate<Vector3<f32>, sdfu::Sphere<f32>>>>,
 }
 
 impl Default for Waves {
     fn default() -> Self {
+        Self::new(Mode::Water)
+    }
+}
+
+impl std::fmt::Debug for Waves {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Waves")
+            .field("mode", &self.mode)
+            .field("boundary", &self.boundary)
+            .finish()
+    }
+}
+
+impl Waves {
+    pub fn new(mode: Mode) -> Self {
         Self {
+            mode,
+            boundary: Boundary::Reflecting,
+            colours: ColourMap::diverging(),
             c2: 0.3,
             h2: 3.0,
             drop_speed: 0.1,
             delta_t: 0.2,
             drop_volume: 10.0,
             drain_rate: 0.1,
+            drop_blend: 0.5,
             max_gradient: 20.0,
             current_volume: 0.0,
+            impulse_rate: 0.03,
+            impulse_strength: 3.0,
+            amplitude: 2.0,
+            damping: 0.995,
             u: Default::default(),
             u_new: Default::default(),
             v: Default::default(),
             drops: Default::default(),
+            field: vec![0.0; SIZE * SIZE * SIZE],
+            field_new: vec![0.0; SIZE * SIZE * SIZE],
+            field_v: vec![0.0; SIZE * SIZE * SIZE],
             sdf_cache: Default::default(),
         }
     }
-}
 
-impl std::fmt::Debug for Waves {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("Waves").finish()
+    pub fn with_boundary(mut self, boundary: Boundary) -> Self {
+        self.boundary = boundary;
+        self
+    }
+
+    pub fn with_colours(mut self, colours: ColourMap) -> Self {
+        self.colours = colours;
+        self
+    }
+
+    pub fn with_impulses(mut self, rate: f32, strength: f32) -> Self {
+        self.impulse_rate = rate;
+        self.impulse_strength = strength;
+        self
+    }
+
+    pub fn with_amplitude(mut self, amplitude: f32) -> Self {
+        self.amplitude = amplitude;
+        self
+    }
+
+    pub fn with_damping(mut self, damping: f32) -> Self {
+        self.damping = damping;
+        self
     }
-}
 
-impl Waves {
     fn u(&self, x: usize, dx: isize, y: usize, dy: isize) -> f32 {
         fn u_inner(this: &Waves, x: usize, dx: isize, y: usize, dy: isize) -> Option<f32> {
             let x = x.checked_add_signed(dx)?;
@@ -70,10 +171,100 @@ impl Waves {
 
         u_inner(self, x, dx, y, dy).unwrap_or_else(|| self.u[(x, y)])
     }
-}
 
-impl Animation for Waves {
-    fn next_frame(&mut self, frame: &mut Frame) {
+    /// The field one voxel along `axis` from `at`, going past the sides as
+    /// `boundary` says.
+    fn neighbour(&self, at: [usize; 3], axis: usize, step: isize) -> f32 {
+        let mut next = at;
+        let c = at[axis] as isize + step;
+
+        if (0..SIZE as isize).contains(&c) {
+            next[axis] = c as usize;
+        } else {
+            match self.boundary {
+                Boundary::Reflecting => {}
+                Boundary::Absorbing => return 0.0,
+                Boundary::Periodic => next[axis] = c.rem_euclid(SIZE as isize) as usize,
+            }
+        }
+
+        self.field[idx(next[0], next[1], next[2])]
+    }
+
+    fn laplacian(&self, at: [usize; 3]) -> f32 {
+        let centre = self.field[idx(at[0], at[1], at[2])];
+
+        (0..3)
+            .map(|axis| self.neighbour(at, axis, 1) + self.neighbour(at, axis, -1))
+            .sum::<f32>()
+            - 6.0 * centre
+    }
+
+    /// A soft bump around a random voxel.
+    fn impulse(&mut self) {
+        let mut rng = rand::thread_rng();
+        let centre = vector![
+            rng.gen_range(0..SIZE) as f32,
+            rng.gen_range(0..SIZE) as f32,
+            rng.gen_range(0..SIZE) as f32
+        ];
+
+        let bump = itertools::iproduct!(0..SIZE, 0..SIZE, 0..SIZE)
+            .map(|(x, y, z)| {
+                let dist2 = (vector![x as f32, y as f32, z as f32] - centre).norm_squared();
+                self.impulse_strength * (-dist2 / 1.5).exp()
+            })
+            .collect::<Vec<_>>();
+
+        // waves can't flatten out a rise in the level of the whole cube, so
+        // without anywhere for it to go it would build up
+        let level = match self.mode {
+            Mode::Wave => bump.iter().sum::<f32>() / bump.len() as f32,
+            _ => 0.0,
+        };
+
+        for (i, (x, y, z)) in itertools::iproduct!(0..SIZE, 0..SIZE, 0..SIZE).enumerate() {
+            self.field[idx(x, y, z)] += bump[i] - level;
+        }
+    }
+
+    fn volume_frame(&mut self, frame: &mut Frame) {
+        if rand::thread_rng().gen_bool(self.impulse_rate.clamp(0.0, 1.0) as f64) {
+            self.impulse();
+        }
+
+        let k = self.c2 / self.h2;
+
+        for (x, y, z) in itertools::iproduct!(0..SIZE, 0..SIZE, 0..SIZE) {
+            let i = idx(x, y, z);
+            let lap = self.laplacian([x, y, z]);
+
+            self.field_new[i] = match self.mode {
+                Mode::Heat => (self.field[i] + k * lap * self.delta_t) * self.damping,
+                _ => {
+                    self.field_v[i] = (self.field_v[i] + k * lap * self.delta_t) * self.damping;
+                    self.field[i] + self.field_v[i] * self.delta_t
+                }
+            };
+
+            // the outside reads as still, but waves still bounce off that
+            // unless the edge also soaks them up
+            let edge = [x, y, z].iter().any(|&c| c == 0 || c == SIZE - 1);
+            if edge && self.mode == Mode::Wave && self.boundary == Boundary::Absorbing {
+                self.field_v[i] *= 0.8;
+            }
+        }
+
+        std::mem::swap(&mut self.field, &mut self.field_new);
+        self.current_volume = self.field.iter().sum();
+
+        for (x, y, z, pix) in frame.pixels_mut() {
+            let a = self.field[idx(x as usize, y as usize, z as usize)];
+            *pix = self.colours.at(0.5 + a / (2.0 * self.amplitude));
+        }
+    }
+
+    fn water_frame(&mut self, frame: &mut Frame) {
         let mut rng = rand::thread_rng();
 
         for (x, z) in (0..8).cartesian_product(0..8) {
@@ -118,16 +309,17 @@ impl Animation for Waves {
         self.sdf_cache.clear();
 
         for &(x, z, y) in &self.drops {
-            let sdf = sdfu::Sphere::new(0.1, LinSrgba::new(0.0, 0.2, 0.9, 1.0))
-                .translate(vector![x as f32, y, z as f32]);
-
-            self.sdf_cache.push(sdf);
+            self.sdf_cache.push(Bounded::sphere(
+                vector![x as f32, y, z as f32],
+                0.1,
+                LinSrgba::new(0.0, 0.2, 0.9, 1.0),
+            ));
         }
 
         if self.sdf_cache.is_empty() {
             frame.zero();
         } else {
-            let union = MultiUnion::hard(&self.sdf_cache);
+            let union = MultiUnion::smooth(&self.sdf_cache, self.drop_blend);
 
             render_sdf(union, frame);
         }
@@ -156,8 +348,101 @@ impl Animation for Waves {
             frame.set(x, y, z, colour);
         }
     }
+}
+
+impl Animation for Waves {
+    fn next_frame(&mut self, frame: &mut Frame) {
+        match self.mode {
+            Mode::Water => self.water_frame(frame),
+            Mode::Wave | Mode::Heat => self.volume_frame(frame),
+        }
+    }
 
     fn reset(&mut self) {
-        *self = Self::default();
+        *self = Self::new(self.mode)
+            .with_boundary(self.boundary)
+            .with_colours(self.colours)
+            .with_impulses(self.impulse_rate, self.impulse_strength)
+            .with_amplitude(self.amplitude)
+            .with_damping(self.damping);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Volumetric waves with no impulses and nothing lost each step.
+    fn still(mode: Mode, boundary: Boundary) -> Waves {
+        Waves::new(mode)
+            .with_boundary(boundary)
+            .with_impulses(0.0, 0.0)
+            .with_damping(1.0)
+    }
+
+    #[test]
+    fn neighbours_past_the_sides() {
+        let cases = [
+            (Boundary::Reflecting, 1.0, 8.0),
+            (Boundary::Absorbing, 0.0, 0.0),
+            (Boundary::Periodic, 8.0, 1.0),
+        ];
+
+        for (boundary, before_first, after_last) in cases {
+            let mut waves = still(Mode::Wave, boundary);
+            for (x, y, z) in itertools::iproduct!(0..SIZE, 0..SIZE, 0..SIZE) {
+                waves.field[idx(x, y, z)] = x as f32 + 1.0;
+            }
+
+            assert_eq!(
+                waves.neighbour([0, 3, 5], 0, -1),
+                before_first,
+                "{:?}",
+                boundary
+            );
+            assert_eq!(
+                waves.neighbour([7, 3, 5], 0, 1),
+                after_last,
+                "{:?}",
+                boundary
+            );
+            assert_eq!(waves.neighbour([3, 3, 5], 0, 1), 5.0, "{:?}", boundary);
+
+            // the field is the same all along y
+            let below = waves.neighbour([3, 0, 5], 1, -1);
+            if boundary == Boundary::Absorbing {
+                assert_eq!(below, 0.0);
+            } else {
+                assert_eq!(below, 4.0, "{:?}", boundary);
+            }
+        }
+    }
+
+    #[test]
+    fn heat_spreads_and_stays_in() {
+        let mut frame = Frame::new();
+
+        for boundary in [
+            Boundary::Reflecting,
+            Boundary::Periodic,
+            Boundary::Absorbing,
+        ] {
+            let mut waves = still(Mode::Heat, boundary);
+            waves.field[idx(0, 0, 0)] = 10.0;
+
+            for _ in 0..50 {
+                waves.next_frame(&mut frame);
+            }
+
+            let hottest = waves.field.iter().copied().fold(0.0, f32::max);
+            assert!(hottest < 10.0, "{:?}", boundary);
+            assert!(waves.field.iter().all(|&t| t >= 0.0), "{:?}", boundary);
+
+            if boundary == Boundary::Absorbing {
+                assert!(waves.current_volume < 9.0, "{}", waves.current_volume);
+            } else {
+                assert!((waves.current_volume - 10.0).abs() < 1e-3, "{:?}", boundary);
+            }
+        }
     }
 }
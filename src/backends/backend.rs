@@ -1,5 +1,96 @@
+use std::fmt;
+use std::io;
+
 use crate::frame::Frame;
 
+#[derive(Debug)]
+pub enum BackendError {
+    /// The device or connection failed.
+    Io(io::Error),
+    /// The device has gone away, it may come back after reopening the backend.
+    Disconnected,
+    /// The device replied with something we didn't expect.
+    Protocol(String),
+    /// Some of the backends of a `MultiBackend` failed, keyed by their index.
+    Multi(Vec<(usize, BackendError)>),
+}
+
+impl fmt::Display for BackendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BackendError::Io(e) => write!(f, "io error: {}", e),
+            BackendError::Disconnected => write!(f, "device disconnected"),
+            BackendError::Protocol(msg) => write!(f, "protocol error: {}", msg),
+            BackendError::Multi(errors) => {
+                write!(f, "{} backend(s) failed", errors.len())?;
+                for (idx, e) in errors {
+                    write!(f, "; backend {}: {}", idx, e)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for BackendError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BackendError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for BackendError {
+    fn from(e: io::Error) -> Self {
+        BackendError::Io(e)
+    }
+}
+
 pub trait Backend {
-    fn display_frame(&mut self, frame: &Frame);
+    /// Acquire the device, called before the first frame and again when
+    /// reconnecting after a failure.
+    fn open(&mut self) -> Result<(), BackendError> {
+        Ok(())
+    }
+
+    fn display_frame(&mut self, frame: &Frame) -> Result<(), BackendError>;
+
+    /// Push out anything buffered by `display_frame`.
+    fn flush(&mut self) -> Result<(), BackendError> {
+        Ok(())
+    }
+
+    /// Turn every LED off.
+    fn blank(&mut self) -> Result<(), BackendError> {
+        self.display_frame(&Frame::new())?;
+        self.flush()
+    }
+
+    /// Release the device, `open` may be called again afterwards.
+    fn close(&mut self) -> Result<(), BackendError> {
+        Ok(())
+    }
+}
+
+impl<B: Backend + ?Sized> Backend for Box<B> {
+    fn open(&mut self) -> Result<(), BackendError> {
+        (**self).open()
+    }
+
+    fn display_frame(&mut self, frame: &Frame) -> Result<(), BackendError> {
+        (**self).display_frame(frame)
+    }
+
+    fn flush(&mut self) -> Result<(), BackendError> {
+        (**self).flush()
+    }
+
+    fn blank(&mut self) -> Result<(), BackendError> {
+        (**self).blank()
+    }
+
+    fn close(&mut self) -> Result<(), BackendError> {
+        (**self).close()
+    }
 }
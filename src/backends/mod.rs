@@ -1,3 +1,6 @@
 pub mod backend;
 
+pub mod multi;
 pub mod null;
+#[cfg(feature = "serial")]
+pub mod serial;
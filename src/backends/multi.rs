@@ -0,0 +1,297 @@
+use std::time::{Duration, Instant};
+
+use super::backend::{Backend, BackendError};
+use crate::frame::Frame;
+
+struct Sink {
+    backend: Box<dyn Backend + Send + Sync>,
+    failed_at: Option<Instant>,
+}
+
+impl Sink {
+    fn reconnect(&mut self) -> Result<(), BackendError> {
+        self.backend.close()?;
+        self.backend.open()
+    }
+}
+
+/// Forwards each frame to several backends, e.g. the cube itself plus a
+/// network mirror plus a recorder.
+///
+/// A backend which fails is taken out of rotation and reopened once
+/// `reconnect_interval` has passed, the remaining backends keep receiving
+/// frames. An error is only returned when every backend failed.
+pub struct MultiBackend {
+    sinks: Vec<Sink>,
+    reconnect_interval: Duration,
+}
+
+impl Default for MultiBackend {
+    fn default() -> Self {
+        Self {
+            sinks: Vec::new(),
+            reconnect_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+impl MultiBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with<B: Backend + Send + Sync + 'static>(mut self, backend: B) -> Self {
+        self.push(backend);
+        self
+    }
+
+    pub fn with_reconnect_interval(mut self, interval: Duration) -> Self {
+        self.reconnect_interval = interval;
+        self
+    }
+
+    pub fn push<B: Backend + Send + Sync + 'static>(&mut self, backend: B) {
+        self.sinks.push(Sink {
+            backend: Box::new(backend),
+            failed_at: None,
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.sinks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sinks.is_empty()
+    }
+
+    /// The number of backends that are still receiving frames.
+    pub fn active(&self) -> usize {
+        self.sinks.iter().filter(|s| s.failed_at.is_none()).count()
+    }
+
+    /// Run `f` on every healthy backend, taking any that fail out of rotation.
+    fn for_each_sink(
+        &mut self,
+        mut f: impl FnMut(&mut dyn Backend) -> Result<(), BackendError>,
+    ) -> Result<(), BackendError> {
+        let now = Instant::now();
+        let mut errors = Vec::new();
+
+        for (idx, sink) in self.sinks.iter_mut().enumerate() {
+            if let Some(failed_at) = sink.failed_at {
+                if now.duration_since(failed_at) < self.reconnect_interval {
+                    continue;
+                }
+
+                if let Err(e) = sink.reconnect() {
+                    sink.failed_at = Some(now);
+                    errors.push((idx, e));
+                    continue;
+                }
+
+                dbg!("reconnected backend", idx);
+                sink.failed_at = None;
+            }
+
+            if let Err(e) = f(&mut *sink.backend) {
+                dbg!("disabling failed backend", idx, &e);
+                sink.failed_at = Some(now);
+                errors.push((idx, e));
+            }
+        }
+
+        if !errors.is_empty() && self.active() == 0 {
+            return Err(BackendError::Multi(errors));
+        }
+
+        Ok(())
+    }
+}
+
+impl Backend for MultiBackend {
+    fn open(&mut self) -> Result<(), BackendError> {
+        for sink in &mut self.sinks {
+            sink.failed_at = None;
+        }
+
+        self.for_each_sink(|b| b.open())
+    }
+
+    fn display_frame(&mut self, frame: &Frame) -> Result<(), BackendError> {
+        self.for_each_sink(|b| b.display_frame(frame))
+    }
+
+    fn flush(&mut self) -> Result<(), BackendError> {
+        self.for_each_sink(|b| b.flush())
+    }
+
+    fn blank(&mut self) -> Result<(), BackendError> {
+        self.for_each_sink(|b| b.blank())
+    }
+
+    fn close(&mut self) -> Result<(), BackendError> {
+        let mut errors = Vec::new();
+
+        for (idx, sink) in self.sinks.iter_mut().enumerate() {
+            if let Err(e) = sink.backend.close() {
+                errors.push((idx, e));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(BackendError::Multi(errors))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use super::*;
+    use crate::backends::null::NullBackend;
+
+    /// Counts what it's sent and fails everything while `broken` is set, the
+    /// handles are shared so they can be checked after moving it into the
+    /// `MultiBackend`.
+    #[derive(Clone, Default)]
+    struct Stub {
+        opens: Arc<AtomicUsize>,
+        frames: Arc<AtomicUsize>,
+        broken: Arc<AtomicBool>,
+    }
+
+    impl Stub {
+        fn check(&self) -> Result<(), BackendError> {
+            if self.broken.load(Ordering::SeqCst) {
+                Err(BackendError::Disconnected)
+            } else {
+                Ok(())
+            }
+        }
+
+        fn frames(&self) -> usize {
+            self.frames.load(Ordering::SeqCst)
+        }
+
+        fn opens(&self) -> usize {
+            self.opens.load(Ordering::SeqCst)
+        }
+
+        fn set_broken(&self, broken: bool) {
+            self.broken.store(broken, Ordering::SeqCst);
+        }
+    }
+
+    impl Backend for Stub {
+        fn open(&mut self) -> Result<(), BackendError> {
+            self.opens.fetch_add(1, Ordering::SeqCst);
+            self.check()
+        }
+
+        fn display_frame(&mut self, _frame: &Frame) -> Result<(), BackendError> {
+            self.check()?;
+            self.frames.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn frames_go_to_every_backend() {
+        let (a, b) = (Stub::default(), Stub::default());
+        let mut multi = MultiBackend::new()
+            .with(NullBackend)
+            .with(a.clone())
+            .with(b.clone());
+
+        multi.open().unwrap();
+        for _ in 0..3 {
+            multi.display_frame(&Frame::new()).unwrap();
+            multi.flush().unwrap();
+        }
+
+        assert_eq!((a.opens(), b.opens()), (1, 1));
+        assert_eq!((a.frames(), b.frames()), (3, 3));
+        assert_eq!(multi.active(), 3);
+    }
+
+    #[test]
+    fn failed_backends_are_disabled() {
+        let (a, b) = (Stub::default(), Stub::default());
+        let mut multi = MultiBackend::new().with(a.clone()).with(b.clone());
+        multi.open().unwrap();
+
+        a.set_broken(true);
+        multi.display_frame(&Frame::new()).unwrap();
+        assert_eq!(multi.active(), 1);
+
+        // still out of rotation until the reconnect interval has passed
+        a.set_broken(false);
+        multi.display_frame(&Frame::new()).unwrap();
+        assert_eq!((a.frames(), b.frames()), (0, 2));
+        assert_eq!(a.opens(), 1);
+    }
+
+    #[test]
+    fn errors_once_every_backend_failed() {
+        let (a, b) = (Stub::default(), Stub::default());
+        let mut multi = MultiBackend::new().with(a.clone()).with(b.clone());
+        multi.open().unwrap();
+
+        a.set_broken(true);
+        b.set_broken(true);
+        match multi.display_frame(&Frame::new()) {
+            Err(BackendError::Multi(errors)) => {
+                let failed = errors.iter().map(|(idx, _)| *idx).collect::<Vec<_>>();
+                assert_eq!(failed, [0, 1]);
+            }
+            other => panic!("expected both to fail, got {:?}", other),
+        }
+        assert_eq!(multi.active(), 0);
+    }
+
+    #[test]
+    fn failed_backends_are_reconnected() {
+        let (a, b) = (Stub::default(), Stub::default());
+        let mut multi = MultiBackend::new()
+            .with(a.clone())
+            .with(b.clone())
+            .with_reconnect_interval(Duration::ZERO);
+        multi.open().unwrap();
+
+        a.set_broken(true);
+        multi.display_frame(&Frame::new()).unwrap();
+        assert_eq!(multi.active(), 1);
+
+        // a failed reconnect keeps it out
+        multi.display_frame(&Frame::new()).unwrap();
+        assert_eq!((multi.active(), a.opens()), (1, 2));
+
+        a.set_broken(false);
+        multi.display_frame(&Frame::new()).unwrap();
+        assert_eq!((multi.active(), a.opens()), (2, 3));
+        assert_eq!((a.frames(), b.frames()), (1, 3));
+    }
+
+    #[test]
+    fn opening_reenables_everything() {
+        let a = Stub::default();
+        let mut multi = MultiBackend::new().with(NullBackend).with(a.clone());
+        multi.open().unwrap();
+
+        a.set_broken(true);
+        multi.display_frame(&Frame::new()).unwrap();
+        a.set_broken(false);
+        multi.open().unwrap();
+
+        assert_eq!(multi.active(), 2);
+        multi.display_frame(&Frame::new()).unwrap();
+        assert_eq!(a.frames(), 1);
+    }
+}
@@ -1,8 +1,9 @@
-use super::backend::Backend;
+use super::backend::{Backend, BackendError};
 
 pub struct NullBackend;
 
 impl Backend for NullBackend {
-    fn display_frame(&mut self, frame: &crate::frame::Frame) {
+    fn display_frame(&mut self, _frame: &crate::frame::Frame) -> Result<(), BackendError> {
+        Ok(())
     }
 }
@@ -0,0 +1,440 @@
+//! Drives a cube through a microcontroller on the end of a serial port.
+//!
+//! Each frame is sent as one packet:
+//!
+//! ```text
+//! | 0xA5 0x5A | flags: u8 | seq: u8 | len: u16 LE | payload: [u8; len] | crc: u16 LE |
+//! ```
+//!
+//! The payload is the frame's voxels in `Frame::pixels` order as linear RGB
+//! bytes with alpha premultiplied. If bit 0 of `flags` is set the payload is
+//! run length encoded as `(count, r, g, b)` quads instead. The CRC is
+//! CRC-16/CCITT-FALSE over everything from `flags` to the end of the payload.
+//!
+//! When acks are enabled the device answers each packet with `0x06 seq` once
+//! it has been accepted, or `0x15 seq` if it was corrupted. The packet is sent
+//! again after a `0x15` or no answer at all, up to a few times.
+
+use std::io::{self, Read, Write};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serialport::{ClearBuffer, SerialPort};
+
+use super::backend::{Backend, BackendError};
+use crate::frame::Frame;
+
+pub const SYNC: [u8; 2] = [0xA5, 0x5A];
+pub const FLAG_RLE: u8 = 1 << 0;
+pub const ACK: u8 = 0x06;
+pub const NAK: u8 = 0x15;
+
+const MAX_RESENDS: usize = 3;
+
+impl From<serialport::Error> for BackendError {
+    fn from(e: serialport::Error) -> Self {
+        match e.kind() {
+            serialport::ErrorKind::NoDevice => BackendError::Disconnected,
+            _ => BackendError::Io(e.into()),
+        }
+    }
+}
+
+pub fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0xFFFFu16;
+
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+
+    crc
+}
+
+fn rle_encode(raw: &[u8], out: &mut Vec<u8>) {
+    let mut pixels = raw.chunks_exact(3).peekable();
+
+    while let Some(pixel) = pixels.next() {
+        let mut count = 1u8;
+
+        while count < u8::MAX && pixels.peek() == Some(&pixel) {
+            pixels.next();
+            count += 1;
+        }
+
+        out.push(count);
+        out.extend_from_slice(pixel);
+    }
+}
+
+/// Encode `frame` as a packet, replacing the contents of `out`.
+pub fn encode_frame(frame: &Frame, seq: u8, compress: bool, out: &mut Vec<u8>) {
+    let raw = frame
+        .pixels()
+        .flat_map(|(_, _, _, pix)| {
+            [pix.red, pix.green, pix.blue]
+                .map(|c| ((c * pix.alpha).clamp(0.0, 1.0) * 255.0).round() as u8)
+        })
+        .collect::<Vec<_>>();
+
+    let mut payload = Vec::new();
+    let mut flags = 0;
+
+    if compress {
+        rle_encode(&raw, &mut payload);
+
+        if payload.len() < raw.len() {
+            flags |= FLAG_RLE;
+        } else {
+            payload = raw;
+        }
+    } else {
+        payload = raw;
+    }
+
+    out.clear();
+    out.extend_from_slice(&SYNC);
+    out.push(flags);
+    out.push(seq);
+    out.extend_from_slice(&(payload.len() as u16).to_le_bytes());
+    out.extend_from_slice(&payload);
+
+    let crc = crc16(&out[SYNC.len()..]);
+    out.extend_from_slice(&crc.to_le_bytes());
+}
+
+/// Writes frames to a serial device, see the module docs for the protocol.
+///
+/// `SerialBackend::from_port` takes an already open port, so the backend can
+/// be exercised against one end of a pseudo-terminal pair from
+/// `serialport::TTYPort::pair`.
+pub struct SerialBackend {
+    path: Option<String>,
+    baud_rate: u32,
+    // `SerialPort` is only `Send`, the mutex is never contended
+    port: Option<Mutex<Box<dyn SerialPort>>>,
+    compress: bool,
+    ack_timeout: Option<Duration>,
+    seq: u8,
+    buf: Vec<u8>,
+}
+
+impl SerialBackend {
+    pub fn new(path: impl Into<String>, baud_rate: u32) -> Self {
+        Self {
+            path: Some(path.into()),
+            baud_rate,
+            port: None,
+            compress: true,
+            ack_timeout: Some(Duration::from_millis(100)),
+            seq: 0,
+            buf: Vec::new(),
+        }
+    }
+
+    pub fn from_port(port: Box<dyn SerialPort>) -> Self {
+        Self {
+            path: None,
+            baud_rate: 0,
+            port: Some(Mutex::new(port)),
+            compress: true,
+            ack_timeout: Some(Duration::from_millis(100)),
+            seq: 0,
+            buf: Vec::new(),
+        }
+    }
+
+    pub fn with_compression(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
+    }
+
+    /// Wait up to `timeout` for the device to ack each frame, `None` disables
+    /// acks for devices that just consume the stream.
+    pub fn with_acks(mut self, timeout: Option<Duration>) -> Self {
+        self.ack_timeout = timeout;
+        self
+    }
+
+    fn port(&mut self) -> Result<&mut Box<dyn SerialPort>, BackendError> {
+        match &mut self.port {
+            Some(port) => Ok(port.get_mut().unwrap()),
+            None => Err(BackendError::Disconnected),
+        }
+    }
+
+    /// Wait for the device to answer packet `seq`, returning whether it was
+    /// accepted. No answer before the port's timeout counts as a rejection.
+    fn read_ack(port: &mut Box<dyn SerialPort>, seq: u8) -> Result<bool, BackendError> {
+        match Self::read_answer(port, seq) {
+            // either the packet or its answer was lost, so send it again
+            Err(e) if e.kind() == io::ErrorKind::TimedOut => Ok(false),
+            result => Ok(result?),
+        }
+    }
+
+    fn read_answer(port: &mut Box<dyn SerialPort>, seq: u8) -> io::Result<bool> {
+        let mut byte = [0u8];
+
+        loop {
+            port.read_exact(&mut byte)?;
+
+            let accepted = match byte[0] {
+                ACK => true,
+                NAK => false,
+                // anything else is noise from the device, skip over it
+                _ => continue,
+            };
+
+            port.read_exact(&mut byte)?;
+
+            // an answer to an earlier packet that we already gave up on
+            if byte[0] != seq {
+                continue;
+            }
+
+            return Ok(accepted);
+        }
+    }
+}
+
+impl Backend for SerialBackend {
+    fn open(&mut self) -> Result<(), BackendError> {
+        if let Some(path) = &self.path {
+            let port = serialport::new(path.as_str(), self.baud_rate)
+                .timeout(self.ack_timeout.unwrap_or(Duration::from_millis(100)))
+                .open()?;
+
+            self.port = Some(Mutex::new(port));
+        }
+
+        let timeout = self.ack_timeout;
+        let port = self.port()?;
+
+        if let Some(timeout) = timeout {
+            port.set_timeout(timeout)?;
+        }
+        port.clear(ClearBuffer::Input)?;
+
+        Ok(())
+    }
+
+    fn display_frame(&mut self, frame: &Frame) -> Result<(), BackendError> {
+        let seq = self.seq;
+        self.seq = self.seq.wrapping_add(1);
+
+        let mut buf = std::mem::take(&mut self.buf);
+        encode_frame(frame, seq, self.compress, &mut buf);
+
+        let ack_timeout = self.ack_timeout;
+        let result = (|| {
+            let port = self.port()?;
+
+            for _ in 0..=MAX_RESENDS {
+                port.write_all(&buf)?;
+
+                if ack_timeout.is_none() || Self::read_ack(port, seq)? {
+                    return Ok(());
+                }
+
+                dbg!("frame not accepted by device, resending", seq);
+            }
+
+            Err(BackendError::Protocol(format!(
+                "frame {} not accepted after {} tries",
+                seq,
+                MAX_RESENDS + 1
+            )))
+        })();
+
+        self.buf = buf;
+        result
+    }
+
+    fn flush(&mut self) -> Result<(), BackendError> {
+        self.port()?.flush()?;
+        Ok(())
+    }
+
+    fn close(&mut self) -> Result<(), BackendError> {
+        // ports handed to us by `from_port` can't be reopened, so hang onto them
+        if self.path.is_some() {
+            self.port = None;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use palette::LinSrgba;
+
+    use super::*;
+
+    fn rle_decode(encoded: &[u8]) -> Vec<u8> {
+        encoded
+            .chunks_exact(4)
+            .flat_map(|run| run[1..].repeat(run[0] as usize))
+            .collect()
+    }
+
+    /// Every voxel a different colour, so nothing repeats.
+    fn noisy_frame() -> Frame {
+        let mut frame = Frame::new();
+
+        for (x, y, z, pix) in frame.pixels_mut() {
+            *pix = LinSrgba::new(x as f32 / 7.0, y as f32 / 7.0, z as f32 / 7.0, 1.0);
+        }
+
+        frame
+    }
+
+    #[test]
+    fn crc16_is_ccitt_false() {
+        assert_eq!(crc16(b"123456789"), 0x29B1);
+        assert_eq!(crc16(b""), 0xFFFF);
+    }
+
+    #[test]
+    fn rle_round_trips() {
+        // a run too long for one count, then a short run, then singles
+        let mut raw = [1, 2, 3].repeat(300);
+        raw.extend([4, 5, 6].repeat(2));
+        raw.extend([7, 8, 9, 1, 2, 3]);
+
+        let mut encoded = Vec::new();
+        rle_encode(&raw, &mut encoded);
+
+        assert_eq!(
+            encoded,
+            [
+                [255, 1, 2, 3],
+                [45, 1, 2, 3],
+                [2, 4, 5, 6],
+                [1, 7, 8, 9],
+                [1, 1, 2, 3]
+            ]
+            .concat()
+        );
+        assert_eq!(rle_decode(&encoded), raw);
+    }
+
+    #[test]
+    fn incompressible_frames_are_sent_raw() {
+        let mut packet = Vec::new();
+        encode_frame(&noisy_frame(), 0, true, &mut packet);
+
+        assert_eq!(packet[2] & FLAG_RLE, 0);
+        assert_eq!(packet.len(), 6 + Frame::LAYERS.pow(3) * 3 + 2);
+
+        let mut raw = Vec::new();
+        rle_encode(&packet[6..packet.len() - 2], &mut raw);
+        assert_eq!(rle_decode(&raw), &packet[6..packet.len() - 2]);
+    }
+
+    #[test]
+    fn packet_layout() {
+        let mut packet = Vec::new();
+        encode_frame(&Frame::new(), 7, true, &mut packet);
+
+        // a blank frame is 512 black voxels, which is runs of 255, 255 and 2
+        let payload = [[255, 0, 0, 0], [255, 0, 0, 0], [2, 0, 0, 0]].concat();
+
+        assert_eq!(packet[..2], SYNC);
+        assert_eq!(packet[2], FLAG_RLE);
+        assert_eq!(packet[3], 7);
+        assert_eq!(packet[4..6], (payload.len() as u16).to_le_bytes());
+        assert_eq!(packet[6..packet.len() - 2], payload);
+
+        let crc = crc16(&packet[2..packet.len() - 2]);
+        assert_eq!(packet[packet.len() - 2..], crc.to_le_bytes());
+    }
+
+    /// Against one end of a pseudo-terminal pair, the other standing in for
+    /// the device.
+    #[cfg(unix)]
+    mod pty {
+        use std::thread;
+
+        use serialport::TTYPort;
+
+        use super::*;
+
+        /// Read one packet off the device end of the pair.
+        fn read_packet(device: &mut TTYPort) -> Vec<u8> {
+            let mut packet = vec![0u8; 6];
+            device.read_exact(&mut packet).unwrap();
+
+            let len = u16::from_le_bytes([packet[4], packet[5]]) as usize;
+            packet.resize(6 + len + 2, 0);
+            device.read_exact(&mut packet[6..]).unwrap();
+
+            packet
+        }
+
+        fn backend_pair(ack_timeout: Duration) -> (SerialBackend, TTYPort) {
+            let (host, mut device) = TTYPort::pair().unwrap();
+            device.set_timeout(Duration::from_secs(5)).unwrap();
+
+            let mut backend = SerialBackend::from_port(Box::new(host)).with_acks(Some(ack_timeout));
+            backend.open().unwrap();
+
+            (backend, device)
+        }
+
+        #[test]
+        fn resends_after_a_nak() {
+            let (mut backend, mut device) = backend_pair(Duration::from_secs(5));
+
+            // scoped so the device end stays open until the backend is done with
+            // it, reads on the host end fail once it closes
+            thread::scope(|scope| {
+                let answers = scope.spawn(|| {
+                    let first = read_packet(&mut device);
+                    device.write_all(&[NAK, first[3]]).unwrap();
+
+                    let second = read_packet(&mut device);
+                    device.write_all(&[ACK, second[3]]).unwrap();
+
+                    (first, second)
+                });
+
+                backend.display_frame(&noisy_frame()).unwrap();
+
+                let (first, second) = answers.join().unwrap();
+                assert_eq!(first, second);
+            });
+        }
+
+        #[test]
+        fn gives_up_when_never_answered() {
+            let (mut backend, mut device) = backend_pair(Duration::from_millis(50));
+
+            thread::scope(|scope| {
+                let packets = scope.spawn(|| {
+                    (0..=MAX_RESENDS)
+                        .map(|_| read_packet(&mut device))
+                        .collect::<Vec<_>>()
+                });
+
+                let result = backend.display_frame(&Frame::new());
+                assert!(
+                    matches!(result, Err(BackendError::Protocol(_))),
+                    "{:?}",
+                    result
+                );
+
+                let packets = packets.join().unwrap();
+                assert!(packets.iter().all(|p| *p == packets[0]));
+            });
+        }
+    }
+}
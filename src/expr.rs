@@ -0,0 +1,386 @@
+//! A small parser and evaluator for arithmetic expressions such as
+//! `sin(2 * t) + cos(a) ^ 2`, so curves and the like can be typed in rather
+//! than written in Rust.
+//!
+//! Supports numbers, named variables, `+ - * / ^`, unary minus, brackets,
+//! the constants `pi`, `tau` and `e`, and the functions `sin`, `cos`, `tan`,
+//! `sqrt`, `abs`, `exp`, `ln` and `floor`.
+
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// Byte offset into the source where things went wrong.
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Func {
+    Sin,
+    Cos,
+    Tan,
+    Sqrt,
+    Abs,
+    Exp,
+    Ln,
+    Floor,
+}
+
+impl Func {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "sin" => Func::Sin,
+            "cos" => Func::Cos,
+            "tan" => Func::Tan,
+            "sqrt" => Func::Sqrt,
+            "abs" => Func::Abs,
+            "exp" => Func::Exp,
+            "ln" => Func::Ln,
+            "floor" => Func::Floor,
+            _ => return None,
+        })
+    }
+
+    fn apply(self, x: f32) -> f32 {
+        match self {
+            Func::Sin => x.sin(),
+            Func::Cos => x.cos(),
+            Func::Tan => x.tan(),
+            Func::Sqrt => x.sqrt(),
+            Func::Abs => x.abs(),
+            Func::Exp => x.exp(),
+            Func::Ln => x.ln(),
+            Func::Floor => x.floor(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Num(f32),
+    /// Index into the variables given to `eval`.
+    Var(usize),
+    Neg(Box<Node>),
+    Add(Box<Node>, Box<Node>),
+    Sub(Box<Node>, Box<Node>),
+    Mul(Box<Node>, Box<Node>),
+    Div(Box<Node>, Box<Node>),
+    Pow(Box<Node>, Box<Node>),
+    Call(Func, Box<Node>),
+}
+
+impl Node {
+    fn eval(&self, vars: &[f32]) -> f32 {
+        match self {
+            Node::Num(n) => *n,
+            Node::Var(i) => vars[*i],
+            Node::Neg(a) => -a.eval(vars),
+            Node::Add(a, b) => a.eval(vars) + b.eval(vars),
+            Node::Sub(a, b) => a.eval(vars) - b.eval(vars),
+            Node::Mul(a, b) => a.eval(vars) * b.eval(vars),
+            Node::Div(a, b) => a.eval(vars) / b.eval(vars),
+            Node::Pow(a, b) => a.eval(vars).powf(b.eval(vars)),
+            Node::Call(f, a) => f.apply(a.eval(vars)),
+        }
+    }
+}
+
+/// A parsed expression, evaluated with values for the variables it was
+/// parsed with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr {
+    root: Node,
+    vars: usize,
+}
+
+impl Expr {
+    /// Parse `src`, which may use any of `vars` by name.
+    pub fn parse(src: &str, vars: &[&str]) -> Result<Self, ParseError> {
+        let mut parser = Parser {
+            src,
+            pos: 0,
+            vars,
+            depth: 0,
+        };
+
+        let root = parser.expr()?;
+        parser.skip_space();
+
+        if parser.pos < src.len() {
+            return Err(parser.error("unexpected input"));
+        }
+
+        Ok(Self {
+            root,
+            vars: vars.len(),
+        })
+    }
+
+    /// `vars` are in the same order as the names given to `parse`.
+    pub fn eval(&self, vars: &[f32]) -> f32 {
+        assert_eq!(vars.len(), self.vars, "wrong number of variables");
+
+        self.root.eval(vars)
+    }
+}
+
+/// Deeper than this is refused rather than risking the stack.
+const MAX_DEPTH: usize = 64;
+
+/// Recursive descent, one method per precedence level.
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+    vars: &'a [&'a str],
+    /// Brackets, minuses and powers currently open.
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> ParseError {
+        ParseError {
+            position: self.pos,
+            message: message.to_owned(),
+        }
+    }
+
+    fn rest(&self) -> &str {
+        &self.src[self.pos..]
+    }
+
+    fn skip_space(&mut self) {
+        let trimmed = self.rest().trim_start();
+        self.pos = self.src.len() - trimmed.len();
+    }
+
+    /// Consume `c` if it's next.
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_space();
+
+        if self.rest().starts_with(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consume a run of characters matching `pred`.
+    fn take_while(&mut self, pred: impl Fn(char) -> bool) -> &'a str {
+        let start = self.pos;
+        let len = self.rest().find(|c| !pred(c)).unwrap_or(self.rest().len());
+
+        self.pos += len;
+        &self.src[start..self.pos]
+    }
+
+    /// Run `parse` one level deeper, as long as that isn't too deep.
+    fn nested(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<Node, ParseError>,
+    ) -> Result<Node, ParseError> {
+        if self.depth >= MAX_DEPTH {
+            return Err(self.error("too deeply nested"));
+        }
+
+        self.depth += 1;
+        let node = parse(self);
+        self.depth -= 1;
+
+        node
+    }
+
+    fn expr(&mut self) -> Result<Node, ParseError> {
+        let mut node = self.term()?;
+
+        loop {
+            if self.eat('+') {
+                node = Node::Add(Box::new(node), Box::new(self.term()?));
+            } else if self.eat('-') {
+                node = Node::Sub(Box::new(node), Box::new(self.term()?));
+            } else {
+                return Ok(node);
+            }
+        }
+    }
+
+    fn term(&mut self) -> Result<Node, ParseError> {
+        let mut node = self.unary()?;
+
+        loop {
+            if self.eat('*') {
+                node = Node::Mul(Box::new(node), Box::new(self.unary()?));
+            } else if self.eat('/') {
+                node = Node::Div(Box::new(node), Box::new(self.unary()?));
+            } else {
+                return Ok(node);
+            }
+        }
+    }
+
+    /// Minus binds looser than powers, so `-x^2` is `-(x^2)`.
+    fn unary(&mut self) -> Result<Node, ParseError> {
+        if self.eat('-') {
+            Ok(Node::Neg(Box::new(self.nested(Self::unary)?)))
+        } else {
+            self.power()
+        }
+    }
+
+    /// Right associative, `2^3^2` is `2^(3^2)`.
+    fn power(&mut self) -> Result<Node, ParseError> {
+        let base = self.atom()?;
+
+        if self.eat('^') {
+            Ok(Node::Pow(
+                Box::new(base),
+                Box::new(self.nested(Self::unary)?),
+            ))
+        } else {
+            Ok(base)
+        }
+    }
+
+    fn atom(&mut self) -> Result<Node, ParseError> {
+        if self.eat('(') {
+            let node = self.nested(Self::expr)?;
+
+            return if self.eat(')') {
+                Ok(node)
+            } else {
+                Err(self.error("expected ')'"))
+            };
+        }
+
+        self.skip_space();
+        let start = self.pos;
+
+        match self.rest().chars().next() {
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let digits = self.take_while(|c| c.is_ascii_digit() || c == '.');
+
+                digits.parse().map(Node::Num).map_err(|_| ParseError {
+                    position: start,
+                    message: format!("bad number '{}'", digits),
+                })
+            }
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                let name = self.take_while(|c| c.is_alphanumeric() || c == '_');
+
+                if let Some(i) = self.vars.iter().position(|&v| v == name) {
+                    return Ok(Node::Var(i));
+                }
+
+                match name {
+                    "pi" => return Ok(Node::Num(std::f32::consts::PI)),
+                    "tau" => return Ok(Node::Num(std::f32::consts::TAU)),
+                    "e" => return Ok(Node::Num(std::f32::consts::E)),
+                    _ => {}
+                }
+
+                let func = Func::from_name(name).ok_or_else(|| ParseError {
+                    position: start,
+                    message: format!("unknown name '{}'", name),
+                })?;
+
+                if !self.eat('(') {
+                    return Err(self.error("expected '(' after function"));
+                }
+                let arg = self.nested(Self::expr)?;
+                if !self.eat(')') {
+                    return Err(self.error("expected ')'"));
+                }
+
+                Ok(Node::Call(func, Box::new(arg)))
+            }
+            Some(_) => Err(self.error("unexpected character")),
+            None => Err(self.error("unexpected end of expression")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(src: &str) -> f32 {
+        Expr::parse(src, &[]).unwrap().eval(&[])
+    }
+
+    fn error(src: &str) -> ParseError {
+        Expr::parse(src, &["x"]).unwrap_err()
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(eval("1 + 2 * 3"), 7.0);
+        assert_eq!(eval("-2^2"), -4.0);
+        assert_eq!(eval("2^3^2"), 512.0);
+        assert_eq!(eval("1 - 2 - 3"), -4.0);
+        assert_eq!(eval("8 / 4 / 2"), 1.0);
+        assert_eq!(eval("2^-1"), 0.5);
+        assert_eq!(eval("(1 + 2) * 3"), 9.0);
+    }
+
+    #[test]
+    fn functions_and_constants() {
+        assert_eq!(eval("sqrt(16)"), 4.0);
+        assert_eq!(eval("floor(-1.5)"), -2.0);
+        assert_eq!(eval("abs(cos(pi))"), 1.0);
+        assert_eq!(eval("tau / pi"), 2.0);
+        assert_eq!(eval("exp(0)"), 1.0);
+        assert!((eval("ln(e)") - 1.0).abs() < 1.0e-6);
+    }
+
+    #[test]
+    fn variables() {
+        let expr = Expr::parse("x * 2 + y", &["x", "y"]).unwrap();
+        assert_eq!(expr.eval(&[3.0, 1.0]), 7.0);
+
+        // a variable called `e` hides the constant
+        let expr = Expr::parse("e + 1", &["e"]).unwrap();
+        assert_eq!(expr.eval(&[1.0]), 2.0);
+    }
+
+    #[test]
+    fn error_positions() {
+        let check = |src, position, message: &str| {
+            let err = error(src);
+            assert_eq!(
+                (err.position, err.message.as_str()),
+                (position, message),
+                "{}",
+                src
+            );
+        };
+
+        check("sin x", 4, "expected '(' after function");
+        check("(1+2", 4, "expected ')'");
+        check("1..2", 0, "bad number '1..2'");
+        check("1 2", 2, "unexpected input");
+        check("x +", 3, "unexpected end of expression");
+        check("2 * y", 4, "unknown name 'y'");
+        check("1 # 2", 2, "unexpected input");
+    }
+
+    #[test]
+    fn deep_nesting_is_an_error() {
+        let src = "(".repeat(100_000);
+        assert_eq!(error(&src).message, "too deeply nested");
+
+        let src = "-".repeat(100_000) + "1";
+        assert_eq!(error(&src).message, "too deeply nested");
+
+        let src = format!("{}1{}", "(".repeat(MAX_DEPTH), ")".repeat(MAX_DEPTH));
+        assert_eq!(eval(&src), 1.0);
+    }
+}
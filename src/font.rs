@@ -0,0 +1,359 @@
+//! A 5×7 bitmap font and the plumbing to draw it onto the cube's faces.
+//!
+//! Text is rasterised into columns of pixels, bit 0 being the top row, which
+//! is what makes scrolling it cheap.
+
+use palette::{FromColor, Hsva, LinSrgba, Mix, RgbHue, Srgba};
+
+use crate::frame::Frame;
+
+pub const GLYPH_HEIGHT: usize = 7;
+
+/// Columns left blank for a space.
+const SPACE_WIDTH: usize = 3;
+
+/// Each glyph is seven rows, the low five bits of which are pixels with the
+/// leftmost in bit 4.
+#[rustfmt::skip]
+const GLYPHS: &[(char, [u8; GLYPH_HEIGHT])] = &[
+    ('A', [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001]),
+    ('B', [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110]),
+    ('C', [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110]),
+    ('D', [0b11100, 0b10010, 0b10001, 0b10001, 0b10001, 0b10010, 0b11100]),
+    ('E', [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111]),
+    ('F', [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000]),
+    ('G', [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111]),
+    ('H', [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001]),
+    ('I', [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110]),
+    ('J', [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100]),
+    ('K', [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001]),
+    ('L', [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111]),
+    ('M', [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001]),
+    ('N', [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001]),
+    ('O', [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110]),
+    ('P', [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000]),
+    ('Q', [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101]),
+    ('R', [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001]),
+    ('S', [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110]),
+    ('T', [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100]),
+    ('U', [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110]),
+    ('V', [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100]),
+    ('W', [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010]),
+    ('X', [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001]),
+    ('Y', [0b10001, 0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100]),
+    ('Z', [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111]),
+    ('0', [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110]),
+    ('1', [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110]),
+    ('2', [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111]),
+    ('3', [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110]),
+    ('4', [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010]),
+    ('5', [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110]),
+    ('6', [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110]),
+    ('7', [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000]),
+    ('8', [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110]),
+    ('9', [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100]),
+    ('!', [0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00000, 0b00100]),
+    ('?', [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b00000, 0b00100]),
+    ('.', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100]),
+    (',', [0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b00100, 0b01000]),
+    (':', [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b01100, 0b00000]),
+    (';', [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b00100, 0b01000]),
+    ('-', [0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000]),
+    ('+', [0b00000, 0b00100, 0b00100, 0b11111, 0b00100, 0b00100, 0b00000]),
+    ('=', [0b00000, 0b00000, 0b11111, 0b00000, 0b11111, 0b00000, 0b00000]),
+    ('_', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b11111]),
+    ('\'', [0b01100, 0b00100, 0b01000, 0b00000, 0b00000, 0b00000, 0b00000]),
+    ('"', [0b01010, 0b01010, 0b01010, 0b00000, 0b00000, 0b00000, 0b00000]),
+    ('/', [0b00000, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b00000]),
+    ('(', [0b00010, 0b00100, 0b01000, 0b01000, 0b01000, 0b00100, 0b00010]),
+    (')', [0b01000, 0b00100, 0b00010, 0b00010, 0b00010, 0b00100, 0b01000]),
+    ('<', [0b00010, 0b00100, 0b01000, 0b10000, 0b01000, 0b00100, 0b00010]),
+    ('>', [0b01000, 0b00100, 0b00010, 0b00001, 0b00010, 0b00100, 0b01000]),
+    ('*', [0b00000, 0b00100, 0b10101, 0b01110, 0b10101, 0b00100, 0b00000]),
+    ('#', [0b01010, 0b01010, 0b11111, 0b01010, 0b11111, 0b01010, 0b01010]),
+    ('%', [0b11000, 0b11001, 0b00010, 0b00100, 0b01000, 0b10011, 0b00011]),
+    ('♥', [0b00000, 0b01010, 0b11111, 0b11111, 0b01110, 0b00100, 0b00000]),
+];
+
+fn glyph(c: char) -> [u8; GLYPH_HEIGHT] {
+    let c = c.to_ascii_uppercase();
+
+    GLYPHS
+        .iter()
+        .find(|(g, _)| *g == c)
+        .or_else(|| GLYPHS.iter().find(|(g, _)| *g == '?'))
+        .map(|(_, rows)| *rows)
+        .unwrap()
+}
+
+/// A string laid out as columns of pixels, glyphs are trimmed to their ink so
+/// narrow ones like `.` and `1` don't leave gaps.
+#[derive(Clone, Debug, Default)]
+pub struct Text {
+    columns: Vec<u8>,
+}
+
+impl Text {
+    pub fn new(s: &str) -> Self {
+        let mut columns = Vec::new();
+
+        for c in s.chars() {
+            if c.is_whitespace() {
+                columns.extend([0; SPACE_WIDTH]);
+                continue;
+            }
+
+            let rows = glyph(c);
+            let ink = rows.iter().fold(0, |acc, row| acc | row);
+
+            for bit in (0..5).rev().filter(|bit| ink & (1 << bit) != 0) {
+                let column = rows
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, row)| *row & (1 << bit) != 0)
+                    .fold(0, |acc, (r, _)| acc | 1 << r);
+
+                columns.push(column);
+            }
+
+            // a gap before the next glyph
+            columns.push(0);
+        }
+
+        Self { columns }
+    }
+
+    pub fn width(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// The pixels of column `n`, blank outside the text.
+    pub fn column(&self, n: isize) -> u8 {
+        usize::try_from(n)
+            .ok()
+            .and_then(|n| self.columns.get(n))
+            .copied()
+            .unwrap_or(0)
+    }
+}
+
+/// A face of the cube, as seen from outside it. On the side faces text reads
+/// upright, on the top and bottom its top is towards the back.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Face {
+    Front,
+    Right,
+    Back,
+    Left,
+    Top,
+    Bottom,
+}
+
+impl Face {
+    /// The side faces in the order text passes through them when it scrolls
+    /// around the cube.
+    pub const SIDES: [Face; 4] = [Face::Front, Face::Right, Face::Back, Face::Left];
+
+    /// The voxel `across` from the left and `row` from the top of the face,
+    /// `depth` layers in from the outside.
+    pub fn voxel(self, across: usize, row: usize, depth: usize) -> (usize, usize, usize) {
+        let (u, v, d) = (across, 7 - row, depth);
+
+        match self {
+            Face::Front => (u, v, d),
+            Face::Right => (7 - d, v, u),
+            Face::Back => (7 - u, v, 7 - d),
+            Face::Left => (d, v, 7 - u),
+            Face::Top => (u, 7 - d, v),
+            Face::Bottom => (u, d, 7 - v),
+        }
+    }
+}
+
+/// Where text is drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Surface {
+    /// A single face, or with an `inset` a plane parallel to it inside the
+    /// cube.
+    Face { face: Face, inset: u8 },
+    /// Wrapped around the four side faces, sharing the corner columns.
+    Ring,
+}
+
+impl Surface {
+    /// How many columns of text are visible at once.
+    pub fn width(self) -> usize {
+        match self {
+            Surface::Face { .. } => 8,
+            Surface::Ring => Face::SIDES.len() * 7,
+        }
+    }
+
+    /// The face, column on it and inset for visible column `n`.
+    fn place(self, n: usize) -> (Face, usize, usize) {
+        match self {
+            Surface::Face { face, inset } => (face, n, inset as usize),
+            Surface::Ring => (Face::SIDES[n / 7], n % 7, 0),
+        }
+    }
+}
+
+/// How text is coloured, `along` runs from the start to the end of the text
+/// and `down` from its top row to its bottom.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Gradient {
+    Solid(LinSrgba),
+    Horizontal {
+        start: LinSrgba,
+        end: LinSrgba,
+    },
+    Vertical {
+        top: LinSrgba,
+        bottom: LinSrgba,
+    },
+    /// Once round the colour wheel over the length of the text.
+    Rainbow,
+}
+
+impl Gradient {
+    pub fn colour(&self, along: f32, down: f32) -> LinSrgba {
+        match *self {
+            Gradient::Solid(colour) => colour,
+            Gradient::Horizontal { start, end } => start.mix(&end, along),
+            Gradient::Vertical { top, bottom } => top.mix(&bottom, down),
+            Gradient::Rainbow => {
+                let hue = RgbHue::from_degrees(along * 360.0);
+                Srgba::from_color(Hsva::new(hue, 1.0, 1.0, 1.0)).into_linear()
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextStyle {
+    pub colour: Gradient,
+    /// How many layers deep each glyph is extruded.
+    pub depth: u8,
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        Self {
+            colour: Gradient::Solid(LinSrgba::new(1.0, 1.0, 1.0, 1.0)),
+            depth: 1,
+        }
+    }
+}
+
+impl TextStyle {
+    pub fn with_colour(mut self, colour: Gradient) -> Self {
+        self.colour = colour;
+        self
+    }
+
+    pub fn with_depth(mut self, depth: u8) -> Self {
+        self.depth = depth;
+        self
+    }
+}
+
+/// Draw `text` onto `surface` with column `scroll` of the text at its left
+/// edge, only the text's pixels are touched.
+pub fn draw_text(
+    frame: &mut Frame,
+    text: &Text,
+    scroll: isize,
+    surface: Surface,
+    style: &TextStyle,
+) {
+    let width = text.width().max(1) as f32;
+
+    for n in 0..surface.width() {
+        let index = scroll + n as isize;
+        let column = text.column(index);
+
+        if column == 0 {
+            continue;
+        }
+
+        let (face, across, inset) = surface.place(n);
+
+        for row in (0..GLYPH_HEIGHT).filter(|row| column & (1 << row) != 0) {
+            let colour = style
+                .colour
+                .colour(index as f32 / width, row as f32 / (GLYPH_HEIGHT - 1) as f32);
+
+            for depth in inset..(inset + style.depth as usize).min(8) {
+                let (x, y, z) = face.voxel(across, row, depth);
+                frame.set(x, y, z, colour);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lit(frame: &Frame) -> usize {
+        frame.pixels().filter(|(_, _, _, c)| c.alpha > 0.0).count()
+    }
+
+    #[test]
+    fn glyphs_are_trimmed_to_their_ink() {
+        let a = Text::new("A");
+
+        // five columns of ink and a gap, the first being A's left leg
+        assert_eq!(a.width(), 6);
+        assert_eq!(a.column(0), 0b1111110);
+        assert_eq!(a.column(5), 0);
+        assert_eq!((a.column(-1), a.column(6)), (0, 0));
+
+        assert_eq!(Text::new("A A").width(), 6 + SPACE_WIDTH + 6);
+        assert_eq!(Text::new("a").columns, a.columns);
+        assert_eq!(Text::new("~").columns, Text::new("?").columns);
+    }
+
+    #[test]
+    fn ring_faces_share_corners() {
+        for (face, next) in Face::SIDES.iter().zip(Face::SIDES.iter().cycle().skip(1)) {
+            for row in 0..8 {
+                assert_eq!(face.voxel(7, row, 0), next.voxel(0, row, 0), "{:?}", face);
+            }
+        }
+
+        assert_eq!(Face::Front.voxel(0, 0, 0), (0, 7, 0));
+        assert_eq!(Face::Top.voxel(0, 0, 0), (0, 7, 7));
+    }
+
+    #[test]
+    fn draws_each_pixel_once_per_layer() {
+        let text = Text::new("A");
+        let pixels = (0..text.width() as isize)
+            .map(|n| text.column(n).count_ones() as usize)
+            .sum::<usize>();
+
+        let mut frame = Frame::new();
+        let face = Surface::Face {
+            face: Face::Front,
+            inset: 0,
+        };
+        draw_text(&mut frame, &text, 0, face, &TextStyle::default());
+        assert_eq!(lit(&frame), pixels);
+
+        let mut frame = Frame::new();
+        draw_text(
+            &mut frame,
+            &text,
+            0,
+            face,
+            &TextStyle::default().with_depth(3),
+        );
+        assert_eq!(lit(&frame), pixels * 3);
+
+        // scrolled off to the left
+        let mut frame = Frame::new();
+        draw_text(&mut frame, &text, 6, face, &TextStyle::default());
+        assert_eq!(lit(&frame), 0);
+    }
+}
@@ -0,0 +1,515 @@
+//! Triangle meshes from OBJ and STL files, and plain point clouds, turned
+//! into voxels.
+//!
+//! Shapes are voxelized into a fine grid first, so when the grid is shrunk to
+//! the cube each voxel's alpha is how much of it the shape covers.
+
+use std::path::Path;
+
+use bitvec::vec::BitVec;
+use nalgebra::{Rotation3, Vector3};
+use palette::LinSrgba;
+
+use super::ImportError;
+use crate::volume::Volume;
+
+fn format_error(msg: impl Into<String>) -> ImportError {
+    ImportError::Format(msg.into())
+}
+
+/// Vertices and the triangles between them, a point cloud is a mesh without
+/// any triangles.
+#[derive(Clone, Debug, Default)]
+pub struct Mesh {
+    pub vertices: Vec<Vector3<f32>>,
+    pub triangles: Vec<[usize; 3]>,
+}
+
+impl Mesh {
+    /// Turn the mesh, for files which don't have y up.
+    pub fn rotate(&mut self, rotation: &Rotation3<f32>) {
+        for v in &mut self.vertices {
+            *v = rotation * *v;
+        }
+    }
+
+    fn bounds(&self) -> Option<(Vector3<f32>, Vector3<f32>)> {
+        let first = *self.vertices.first()?;
+
+        Some(
+            self.vertices
+                .iter()
+                .fold((first, first), |(lo, hi), v| (lo.inf(v), hi.sup(v))),
+        )
+    }
+}
+
+/// Read an OBJ, only the vertex positions and faces are used. Faces with
+/// more than three sides are split into fans.
+pub fn parse_obj(src: &str) -> Result<Mesh, ImportError> {
+    let mut mesh = Mesh::default();
+
+    for (n, line) in src.lines().enumerate() {
+        let bad_line = || format_error(format!("bad OBJ on line {}", n + 1));
+        let mut words = line.split_whitespace();
+
+        match words.next() {
+            Some("v") => {
+                let mut coords = words
+                    .take(3)
+                    .map(|w| w.parse::<f32>().map_err(|_| bad_line()));
+                let mut coord = || coords.next().unwrap_or_else(|| Err(bad_line()));
+
+                mesh.vertices
+                    .push(Vector3::new(coord()?, coord()?, coord()?));
+            }
+            Some("f") => {
+                let count = mesh.vertices.len() as isize;
+
+                let face = words
+                    .map(|w| {
+                        // `v/vt/vn`, indices start at one and negative ones
+                        // count back from the latest vertex
+                        let idx = w
+                            .split('/')
+                            .next()
+                            .and_then(|i| i.parse::<isize>().ok())
+                            .ok_or_else(bad_line)?;
+                        let idx = if idx < 0 { count + idx } else { idx - 1 };
+
+                        if (0..count).contains(&idx) {
+                            Ok(idx as usize)
+                        } else {
+                            Err(bad_line())
+                        }
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                if face.len() < 3 {
+                    return Err(bad_line());
+                }
+
+                for i in 1..face.len() - 1 {
+                    mesh.triangles.push([face[0], face[i], face[i + 1]]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(mesh)
+}
+
+/// Read a binary or ASCII STL.
+pub fn parse_stl(bytes: &[u8]) -> Result<Mesh, ImportError> {
+    // binary files may start with "solid" too, so go by the size. A text
+    // file's "count" can be huge, too big to be the size of anything
+    let binary = bytes.len() >= 84 && {
+        let count = u32::from_le_bytes(bytes[80..84].try_into().unwrap()) as usize;
+
+        count
+            .checked_mul(50)
+            .and_then(|n| n.checked_add(84))
+            .is_some_and(|size| bytes.len() == size)
+    };
+
+    let mut mesh = Mesh::default();
+
+    if binary {
+        for tri in bytes[84..].chunks_exact(50) {
+            let start = mesh.vertices.len();
+
+            // skip the normal, then three vertices
+            for v in tri[12..48].chunks_exact(12) {
+                let f = |i: usize| f32::from_le_bytes(v[i * 4..i * 4 + 4].try_into().unwrap());
+                mesh.vertices.push(Vector3::new(f(0), f(1), f(2)));
+            }
+
+            mesh.triangles.push([start, start + 1, start + 2]);
+        }
+    } else {
+        let src = std::str::from_utf8(bytes)
+            .map_err(|_| format_error("STL is neither binary nor text"))?;
+
+        for line in src.lines() {
+            let mut words = line.split_whitespace();
+
+            if words.next() != Some("vertex") {
+                continue;
+            }
+
+            let coords = words
+                .map(|w| w.parse::<f32>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| format_error("bad STL vertex"))?;
+
+            if coords.len() != 3 {
+                return Err(format_error("bad STL vertex"));
+            }
+
+            mesh.vertices
+                .push(Vector3::new(coords[0], coords[1], coords[2]));
+        }
+
+        if mesh.vertices.len() % 3 != 0 {
+            return Err(format_error("STL facet without three vertices"));
+        }
+
+        mesh.triangles = (0..mesh.vertices.len() / 3)
+            .map(|i| [i * 3, i * 3 + 1, i * 3 + 2])
+            .collect();
+    }
+
+    Ok(mesh)
+}
+
+/// Read a point cloud with one `x y z` point per line, separated by spaces
+/// or commas. Anything after the third number is ignored, as are blank lines
+/// and ones starting with `#`.
+pub fn parse_points(src: &str) -> Result<Mesh, ImportError> {
+    let mut mesh = Mesh::default();
+
+    for (n, line) in src.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let coords = line
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|w| !w.is_empty())
+            .take(3)
+            .map(|w| w.parse::<f32>())
+            .collect::<Result<Vec<_>, _>>()
+            .ok()
+            .filter(|coords| coords.len() == 3)
+            .ok_or_else(|| format_error(format!("bad point on line {}", n + 1)))?;
+
+        mesh.vertices
+            .push(Vector3::new(coords[0], coords[1], coords[2]));
+    }
+
+    Ok(mesh)
+}
+
+/// Load a mesh or point cloud, picking the format from the extension:
+/// `.obj`, `.stl`, or anything else for points.
+pub fn load(path: impl AsRef<Path>) -> Result<Mesh, ImportError> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path)?;
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+
+    let text = || std::str::from_utf8(&bytes).map_err(|_| format_error("file isn't text"));
+
+    match ext.as_deref() {
+        Some("obj") => parse_obj(text()?),
+        Some("stl") => parse_stl(&bytes),
+        _ => parse_points(text()?),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fill {
+    /// Only the voxels the surface passes through.
+    Surface,
+    /// Everything inside the mesh too, which needs it to be closed.
+    Solid,
+}
+
+/// Which cells of a cube shaped grid are filled.
+#[derive(Clone, Debug)]
+pub struct Voxels {
+    resolution: usize,
+    bits: BitVec,
+}
+
+impl Voxels {
+    pub fn new(resolution: usize) -> Self {
+        Self {
+            resolution,
+            bits: BitVec::repeat(false, resolution.pow(3)),
+        }
+    }
+
+    pub fn resolution(&self) -> usize {
+        self.resolution
+    }
+
+    fn index(&self, x: usize, y: usize, z: usize) -> usize {
+        x + self.resolution * (y + self.resolution * z)
+    }
+
+    pub fn get(&self, x: usize, y: usize, z: usize) -> bool {
+        self.bits[self.index(x, y, z)]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, z: usize, val: bool) {
+        let idx = self.index(x, y, z);
+        self.bits.set(idx, val);
+    }
+
+    fn mark(&mut self, p: Vector3<f32>) {
+        let max = (self.resolution - 1) as f32;
+        let [x, y, z] = [p.x, p.y, p.z].map(|c| c.floor().clamp(0.0, max) as usize);
+
+        self.set(x, y, z, true);
+    }
+
+    /// Colour the filled cells, ready to be scaled with `Volume::fit`.
+    pub fn to_volume(&self, colour: LinSrgba) -> Volume {
+        let mut volume = Volume::new([self.resolution; 3]);
+
+        for z in 0..self.resolution {
+            for y in 0..self.resolution {
+                for x in 0..self.resolution {
+                    if self.get(x, y, z) {
+                        volume.set(x, y, z, colour);
+                    }
+                }
+            }
+        }
+
+        volume
+    }
+}
+
+/// Scale `mesh` so its bounding box fits a grid `resolution` cells across,
+/// centred, and fill the cells it covers.
+pub fn voxelize(mesh: &Mesh, resolution: usize, fill: Fill) -> Voxels {
+    let mut voxels = Voxels::new(resolution);
+
+    let (lo, hi) = match mesh.bounds() {
+        Some(bounds) if resolution > 0 => bounds,
+        _ => return voxels,
+    };
+
+    let extent = hi - lo;
+    let scale = resolution as f32 / extent.max().max(f32::EPSILON);
+    let offset = (Vector3::repeat(resolution as f32) - extent * scale) / 2.0;
+
+    let vertices = mesh
+        .vertices
+        .iter()
+        .map(|v| (v - lo) * scale + offset)
+        .collect::<Vec<_>>();
+
+    for &v in &vertices {
+        voxels.mark(v);
+    }
+
+    for &[a, b, c] in &mesh.triangles {
+        let (a, b, c) = (vertices[a], vertices[b], vertices[c]);
+
+        // sample the triangle at under half a cell apart so no cell it
+        // crosses is missed
+        let longest = (b - a).norm().max((c - b).norm()).max((a - c).norm());
+        let steps = (longest * 2.0).ceil().max(1.0) as usize;
+
+        for i in 0..=steps {
+            for j in 0..=steps - i {
+                let (u, v) = (i as f32 / steps as f32, j as f32 / steps as f32);
+                voxels.mark(a + (b - a) * u + (c - a) * v);
+            }
+        }
+    }
+
+    if fill == Fill::Solid {
+        fill_solid(&mut voxels, &vertices, &mesh.triangles);
+    }
+
+    voxels
+}
+
+/// Cast a ray along z through the middle of every column and fill between
+/// pairs of crossings of the surface.
+fn fill_solid(voxels: &mut Voxels, vertices: &[Vector3<f32>], triangles: &[[usize; 3]]) {
+    let res = voxels.resolution;
+    let mut crossings = vec![Vec::new(); res * res];
+
+    for &[a, b, c] in triangles {
+        let (a, b, c) = (vertices[a], vertices[b], vertices[c]);
+
+        let det = (b.x - a.x) * (c.y - a.y) - (c.x - a.x) * (b.y - a.y);
+        if det.abs() < f32::EPSILON {
+            // edge on to the rays
+            continue;
+        }
+
+        let lo = a.xy().inf(&b.xy()).inf(&c.xy());
+        let hi = a.xy().sup(&b.xy()).sup(&c.xy());
+        let cells = |lo: f32, hi: f32| {
+            let start = (lo - 0.5).ceil().max(0.0) as usize;
+            let end = ((hi - 0.5).floor() + 1.0).clamp(0.0, res as f32) as usize;
+            start..end
+        };
+
+        for y in cells(lo.y, hi.y) {
+            for x in cells(lo.x, hi.x) {
+                // nudged off the centre so rays don't run exactly along the
+                // edges of axis aligned faces
+                let (px, py) = (x as f32 + 0.5 + 1.3e-4, y as f32 + 0.5 + 0.7e-4);
+
+                let u = ((px - a.x) * (c.y - a.y) - (c.x - a.x) * (py - a.y)) / det;
+                let v = ((b.x - a.x) * (py - a.y) - (px - a.x) * (b.y - a.y)) / det;
+
+                if u >= 0.0 && v >= 0.0 && u + v < 1.0 {
+                    let z = a.z + (b.z - a.z) * u + (c.z - a.z) * v;
+                    crossings[x + res * y].push(z);
+                }
+            }
+        }
+    }
+
+    for y in 0..res {
+        for x in 0..res {
+            let column = &mut crossings[x + res * y];
+            column.sort_by(f32::total_cmp);
+
+            for pair in column.chunks_exact(2) {
+                let start = (pair[0] - 0.5).ceil().max(0.0) as usize;
+                let end = ((pair[1] - 0.5).floor() + 1.0).clamp(0.0, res as f32) as usize;
+
+                for z in start..end {
+                    voxels.set(x, y, z, true);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A closed cube two units across, with quad faces.
+    const CUBE_OBJ: &str = "\
+v 0 0 0
+v 2 0 0
+v 2 2 0
+v 0 2 0
+v 0 0 2
+v 2 0 2
+v 2 2 2
+v 0 2 2
+f 1 4 3 2
+f 5 6 7 8
+f 1 2 6 5
+f 3 4 8 7
+f 1 5 8 4
+f 2 3 7 6
+";
+
+    #[test]
+    fn obj_faces_are_split_into_fans() {
+        let mesh = parse_obj("v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nv 0 2 0\nf 1 2 3 4 5\n").unwrap();
+
+        assert_eq!(mesh.vertices.len(), 5);
+        assert_eq!(mesh.triangles, [[0, 1, 2], [0, 2, 3], [0, 3, 4]]);
+    }
+
+    #[test]
+    fn obj_indices() {
+        // negative indices count back from the latest vertex, and texture and
+        // normal indices are ignored
+        let mesh = parse_obj("v 0 0 0\nv 1 0 0\nv 0 1 0\nf -3/1/1 -2//2 -1\n").unwrap();
+        assert_eq!(mesh.triangles, [[0, 1, 2]]);
+
+        assert!(parse_obj("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n").is_err());
+        assert!(parse_obj("v 0 0 0\nv 1 0 0\nf 1 2\n").is_err());
+        assert!(parse_obj("v 0 zero 0\n").is_err());
+    }
+
+    #[test]
+    fn ascii_stl() {
+        let src = "\
+solid tri
+  facet normal 0 0 1
+    outer loop
+      vertex 0 0 0
+      vertex 1 0 0
+      vertex 0 1 0
+    endloop
+  endfacet
+  facet normal 0 0 1
+    outer loop
+      vertex 1 0 0
+      vertex 1 1 0
+      vertex 0 1 0
+    endloop
+  endfacet
+endsolid tri
+";
+        // long enough that bytes 80..84 are read as a triangle count
+        assert!(src.len() > 84);
+
+        let mesh = parse_stl(src.as_bytes()).unwrap();
+
+        assert_eq!(mesh.vertices.len(), 6);
+        assert_eq!(mesh.triangles, [[0, 1, 2], [3, 4, 5]]);
+        assert_eq!(mesh.vertices[4], Vector3::new(1.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn binary_stl() {
+        let mut bytes = vec![0u8; 80];
+        bytes.extend(1u32.to_le_bytes());
+
+        // the normal, then three vertices
+        for f in [
+            0.0f32, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0,
+        ] {
+            bytes.extend(f.to_le_bytes());
+        }
+        bytes.extend([0, 0]);
+
+        let mesh = parse_stl(&bytes).unwrap();
+
+        assert_eq!(mesh.triangles, [[0, 1, 2]]);
+        assert_eq!(mesh.vertices[1], Vector3::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn points() {
+        let mesh = parse_points("# x y z\n1 2 3\n\n4,5,6,ignored\n  7 8 9  \n").unwrap();
+
+        assert!(mesh.triangles.is_empty());
+        assert_eq!(
+            mesh.vertices,
+            [
+                Vector3::new(1.0, 2.0, 3.0),
+                Vector3::new(4.0, 5.0, 6.0),
+                Vector3::new(7.0, 8.0, 9.0)
+            ]
+        );
+
+        assert!(parse_points("1 2\n").is_err());
+    }
+
+    fn count(voxels: &Voxels) -> usize {
+        voxels.bits.count_ones()
+    }
+
+    #[test]
+    fn cube_surface_and_solid() {
+        let cube = parse_obj(CUBE_OBJ).unwrap();
+
+        // the faces land in the outer layer of cells, the inside is left empty
+        let surface = voxelize(&cube, 6, Fill::Surface);
+        assert_eq!(count(&surface), 6 * 6 * 6 - 4 * 4 * 4);
+        assert!(!surface.get(2, 3, 2));
+
+        let solid = voxelize(&cube, 6, Fill::Solid);
+        assert_eq!(count(&solid), 6 * 6 * 6);
+    }
+
+    #[test]
+    fn zero_resolution_is_empty() {
+        let cube = parse_obj(CUBE_OBJ).unwrap();
+
+        assert_eq!(voxelize(&cube, 0, Fill::Solid).resolution(), 0);
+    }
+}
@@ -0,0 +1,40 @@
+//! Loaders for voxel content made in other tools, each produces `Volume`s.
+
+use std::fmt;
+use std::io;
+
+pub mod mesh;
+pub mod slices;
+pub mod vox;
+
+#[derive(Debug)]
+pub enum ImportError {
+    /// The file couldn't be read.
+    Io(io::Error),
+    /// The file was read but isn't something we understand.
+    Format(String),
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Io(e) => write!(f, "io error: {}", e),
+            ImportError::Format(msg) => write!(f, "bad file: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ImportError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ImportError {
+    fn from(e: io::Error) -> Self {
+        ImportError::Io(e)
+    }
+}
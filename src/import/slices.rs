@@ -0,0 +1,256 @@
+//! Stacks of 2D images, each one a slice through the cube.
+//!
+//! Slices come from PNG files, one per image, or from the frames of an
+//! animated GIF. A directory of them is read in file name order and cut into
+//! groups of `slices_per_frame`, each group becoming one volume, so the same
+//! directory can hold a still model or a video.
+
+use std::path::{Path, PathBuf};
+
+use image::{codecs::gif::GifDecoder, AnimationDecoder, RgbaImage};
+use palette::{LinSrgba, Srgba};
+
+use super::ImportError;
+use crate::frame::Frame;
+use crate::volume::Volume;
+
+impl From<image::ImageError> for ImportError {
+    fn from(e: image::ImageError) -> Self {
+        match e {
+            image::ImageError::IoError(e) => ImportError::Io(e),
+            e => ImportError::Format(e.to_string()),
+        }
+    }
+}
+
+/// The axis the stack runs along, so each image lies across the other two.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Axis {
+    /// Images are seen from the side, the first at the left.
+    X,
+    /// Images are seen from above, the first at the bottom.
+    Y,
+    /// Images are seen from the front, the first at the front.
+    Z,
+}
+
+/// How a stack which isn't 8×8×8 is brought to the cube's size.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scaling {
+    /// Keep proportions, see `Volume::fit`.
+    Fit,
+    /// Fill the cube along every axis.
+    Stretch,
+    /// One pixel per voxel, keeping the middle.
+    Crop,
+}
+
+/// How the images' pixel values are encoded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColourSpace {
+    /// Gamma encoded sRGB, what nearly every image is saved as.
+    Srgb,
+    /// Already linear, for images made as data rather than pictures.
+    Linear,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SliceOptions {
+    pub axis: Axis,
+    pub scaling: Scaling,
+    pub colour_space: ColourSpace,
+    /// How many slices make up each volume, all of them if `None`.
+    pub slices_per_frame: Option<usize>,
+}
+
+impl Default for SliceOptions {
+    fn default() -> Self {
+        Self {
+            axis: Axis::Y,
+            scaling: Scaling::Fit,
+            colour_space: ColourSpace::Srgb,
+            slices_per_frame: None,
+        }
+    }
+}
+
+impl SliceOptions {
+    pub fn with_axis(mut self, axis: Axis) -> Self {
+        self.axis = axis;
+        self
+    }
+
+    pub fn with_scaling(mut self, scaling: Scaling) -> Self {
+        self.scaling = scaling;
+        self
+    }
+
+    pub fn with_colour_space(mut self, colour_space: ColourSpace) -> Self {
+        self.colour_space = colour_space;
+        self
+    }
+
+    pub fn with_slices_per_frame(mut self, slices: usize) -> Self {
+        self.slices_per_frame = Some(slices);
+        self
+    }
+
+    /// Scale `volume` into the cube.
+    pub fn scale(&self, volume: &Volume) -> Frame {
+        match self.scaling {
+            Scaling::Fit => volume.fit(),
+            Scaling::Stretch => volume.stretch(),
+            Scaling::Crop => volume.crop(),
+        }
+    }
+
+    fn colour(&self, [r, g, b, a]: [u8; 4]) -> LinSrgba {
+        match self.colour_space {
+            ColourSpace::Srgb => Srgba::new(r, g, b, a).into_format().into_linear(),
+            ColourSpace::Linear => LinSrgba::new(r, g, b, a).into_format(),
+        }
+    }
+}
+
+/// Stack `slices` into one volume, they must all be the same size.
+pub fn stack(slices: &[RgbaImage], options: &SliceOptions) -> Result<Volume, ImportError> {
+    let (w, h) = match slices.first() {
+        Some(first) => first.dimensions(),
+        None => return Err(ImportError::Format("no slices".to_owned())),
+    };
+
+    if slices.iter().any(|slice| slice.dimensions() != (w, h)) {
+        return Err(ImportError::Format("slices are different sizes".to_owned()));
+    }
+
+    let (w, h, n) = (w as usize, h as usize, slices.len());
+
+    let mut volume = Volume::new(match options.axis {
+        Axis::X => [n, h, w],
+        Axis::Y => [w, n, h],
+        Axis::Z => [w, h, n],
+    });
+
+    for (s, slice) in slices.iter().enumerate() {
+        for (u, v, pixel) in slice.enumerate_pixels() {
+            let (u, v) = (u as usize, v as usize);
+
+            // image rows run downwards, the cube's y runs up
+            let (x, y, z) = match options.axis {
+                Axis::X => (s, h - 1 - v, u),
+                Axis::Y => (u, s, v),
+                Axis::Z => (u, h - 1 - v, s),
+            };
+
+            volume.set(x, y, z, options.colour(pixel.0));
+        }
+    }
+
+    Ok(volume)
+}
+
+/// The frames of an animated GIF, each drawn over the ones before it as a
+/// viewer would show them.
+pub fn load_gif(path: impl AsRef<Path>) -> Result<Vec<RgbaImage>, ImportError> {
+    let file = std::io::BufReader::new(std::fs::File::open(path)?);
+    let frames = GifDecoder::new(file)?.into_frames().collect_frames()?;
+
+    Ok(frames
+        .into_iter()
+        .map(|frame| frame.into_buffer())
+        .collect())
+}
+
+/// Every slice in `path`, which is either a GIF or a PNG.
+pub fn load_slices(path: impl AsRef<Path>) -> Result<Vec<RgbaImage>, ImportError> {
+    let path = path.as_ref();
+
+    if has_extension(path, "gif") {
+        load_gif(path)
+    } else {
+        Ok(vec![image::open(path)?.into_rgba8()])
+    }
+}
+
+fn has_extension(path: &Path, ext: &str) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case(ext))
+}
+
+/// Every PNG and GIF in `dir` in file name order, grouped into volumes.
+pub fn load_dir(dir: impl AsRef<Path>, options: &SliceOptions) -> Result<Vec<Volume>, ImportError> {
+    let mut paths = std::fs::read_dir(dir)?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<Result<Vec<PathBuf>, ImportError>>()?;
+
+    paths.retain(|path| has_extension(path, "png") || has_extension(path, "gif"));
+    paths.sort();
+
+    let mut slices = Vec::new();
+    for path in &paths {
+        slices.extend(load_slices(path)?);
+    }
+
+    let per_frame = options.slices_per_frame.unwrap_or(slices.len()).max(1);
+
+    slices
+        .chunks(per_frame)
+        .map(|group| stack(group, options))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use super::*;
+
+    /// Two 3×2 slices, clear apart from the top right pixel of the second.
+    fn slices() -> Vec<RgbaImage> {
+        let mut slices = vec![RgbaImage::new(3, 2); 2];
+        slices[1].put_pixel(2, 0, Rgba([255, 0, 0, 255]));
+        slices
+    }
+
+    #[test]
+    fn slices_are_laid_along_the_axis() {
+        let red = LinSrgba::new(1.0, 0.0, 0.0, 1.0);
+
+        // the size, then where the marked pixel lands
+        let cases = [
+            (Axis::X, [2, 2, 3], (1, 1, 2)),
+            (Axis::Y, [3, 2, 2], (2, 1, 0)),
+            (Axis::Z, [3, 2, 2], (2, 1, 1)),
+        ];
+
+        for (axis, size, (x, y, z)) in cases {
+            let options = SliceOptions::default()
+                .with_axis(axis)
+                .with_colour_space(ColourSpace::Linear);
+            let volume = stack(&slices(), &options).unwrap();
+
+            assert_eq!(volume.size(), size, "{:?}", axis);
+            assert_eq!(volume.get(x, y, z), red, "{:?}", axis);
+
+            let lit = (0..size[0])
+                .flat_map(|x| (0..size[1]).flat_map(move |y| (0..size[2]).map(move |z| (x, y, z))))
+                .filter(|&(x, y, z)| volume.get(x, y, z).alpha > 0.0)
+                .count();
+            assert_eq!(lit, 1, "{:?}", axis);
+        }
+    }
+
+    #[test]
+    fn mismatched_slices() {
+        let options = SliceOptions::default();
+
+        assert!(matches!(stack(&[], &options), Err(ImportError::Format(_))));
+
+        let slices = [RgbaImage::new(3, 2), RgbaImage::new(2, 3)];
+        assert!(matches!(
+            stack(&slices, &options),
+            Err(ImportError::Format(_))
+        ));
+    }
+}
@@ -0,0 +1,245 @@
+//! MagicaVoxel `.vox` files.
+//!
+//! A file is a `MAIN` chunk whose children hold a `SIZE` and `XYZI` chunk per
+//! model, then optionally an `RGBA` palette. Files saved as animations keep
+//! one model per frame, so the models are returned in the order they appear.
+//! The scene graph, materials and layers are skipped.
+
+use std::path::Path;
+
+use palette::{LinSrgba, Srgba};
+
+use super::ImportError;
+use crate::volume::Volume;
+
+/// MagicaVoxel won't make a model bigger than this along any axis.
+const MAX_SIZE: u32 = 256;
+
+fn format_error(msg: impl Into<String>) -> ImportError {
+    ImportError::Format(msg.into())
+}
+
+/// A chunk's id, content and children.
+type Chunk<'a> = (&'a [u8], &'a [u8], &'a [u8]);
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], ImportError> {
+        if self.bytes.len() < n {
+            return Err(format_error("unexpected end of file"));
+        }
+
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> Result<u32, ImportError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn chunk(&mut self) -> Result<Chunk<'a>, ImportError> {
+        let id = self.take(4)?;
+        let content = self.u32()? as usize;
+        let children = self.u32()? as usize;
+
+        Ok((id, self.take(content)?, self.take(children)?))
+    }
+}
+
+fn colour([r, g, b, a]: [u8; 4]) -> LinSrgba {
+    Srgba::new(r, g, b, a).into_format().into_linear()
+}
+
+/// The palette MagicaVoxel uses when a file doesn't have its own: a 6×6×6
+/// colour cube without black, then ramps of red, green, blue and grey.
+fn default_palette() -> [LinSrgba; 256] {
+    let mut palette = [LinSrgba::new(0.0, 0.0, 0.0, 0.0); 256];
+    let mut entries = palette.iter_mut().skip(1);
+
+    // blue changes fastest
+    let steps = [0xff, 0xcc, 0x99, 0x66, 0x33, 0x00];
+    for r in steps {
+        for g in steps {
+            for b in steps.into_iter().filter(|&b| (r, g, b) != (0, 0, 0)) {
+                *entries.next().unwrap() = colour([r, g, b, 0xff]);
+            }
+        }
+    }
+
+    let ramp = [0xee, 0xdd, 0xbb, 0xaa, 0x88, 0x77, 0x55, 0x44, 0x22, 0x11];
+    for channel in [[1, 0, 0], [0, 1, 0], [0, 0, 1], [1, 1, 1]] {
+        for v in ramp {
+            let [r, g, b] = channel.map(|c| c * v);
+            *entries.next().unwrap() = colour([r, g, b, 0xff]);
+        }
+    }
+
+    palette
+}
+
+pub fn load(path: impl AsRef<Path>) -> Result<Vec<Volume>, ImportError> {
+    parse(&std::fs::read(path)?)
+}
+
+pub fn parse(bytes: &[u8]) -> Result<Vec<Volume>, ImportError> {
+    let mut reader = Reader { bytes };
+
+    if reader.take(4)? != b"VOX " {
+        return Err(format_error("not a .vox file"));
+    }
+    let _version = reader.u32()?;
+
+    let (id, _, children) = reader.chunk()?;
+    if id != b"MAIN" {
+        return Err(format_error("missing MAIN chunk"));
+    }
+
+    let mut sizes = Vec::new();
+    let mut models = Vec::new();
+    let mut palette = default_palette();
+
+    let mut reader = Reader { bytes: children };
+    while !reader.bytes.is_empty() {
+        let (id, content, _) = reader.chunk()?;
+        let mut content = Reader { bytes: content };
+
+        match id {
+            b"SIZE" => {
+                let size = [content.u32()?, content.u32()?, content.u32()?];
+
+                if size.iter().any(|&n| n > MAX_SIZE) {
+                    return Err(format_error(format!("model too big, {:?}", size)));
+                }
+
+                sizes.push(size);
+            }
+            b"XYZI" => {
+                let count = content.u32()? as usize;
+                let len = count
+                    .checked_mul(4)
+                    .ok_or_else(|| format_error("too many voxels"))?;
+
+                models.push(content.take(len)?);
+            }
+            b"RGBA" => {
+                // entry i is colour index i + 1, the last entry is unused
+                for (entry, rgba) in palette[1..].iter_mut().zip(content.bytes.chunks_exact(4)) {
+                    *entry = colour(rgba.try_into().unwrap());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if sizes.len() != models.len() {
+        return Err(format_error(format!(
+            "{} SIZE chunks but {} XYZI chunks",
+            sizes.len(),
+            models.len()
+        )));
+    }
+
+    sizes
+        .into_iter()
+        .zip(models)
+        .map(|([sx, sy, sz], voxels)| {
+            let (sx, sy, sz) = (sx as usize, sy as usize, sz as usize);
+
+            // capped already, but the sizes came from the file
+            if sx.checked_mul(sy).and_then(|n| n.checked_mul(sz)).is_none() {
+                return Err(format_error("model too big"));
+            }
+
+            // MagicaVoxel is z up with y going away from the viewer, swap
+            // those round and flip depth so the model isn't mirrored
+            let mut volume = Volume::new([sx, sz, sy]);
+
+            for voxel in voxels.chunks_exact(4) {
+                let [x, y, z, index] = [0, 1, 2, 3].map(|i| voxel[i] as usize);
+
+                if x >= sx || y >= sy || z >= sz {
+                    return Err(format_error("voxel outside its model"));
+                }
+
+                volume.set(x, z, sy - 1 - y, palette[index]);
+            }
+
+            Ok(volume)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An entry of MagicaVoxel's published default palette, which is
+    /// written as `0xAABBGGRR`.
+    fn abgr(c: u32) -> LinSrgba {
+        let [r, g, b, a] = c.to_le_bytes();
+        colour([r, g, b, a])
+    }
+
+    #[test]
+    fn default_palette_matches_magicavoxel() {
+        let palette = default_palette();
+
+        assert_eq!(palette[0].alpha, 0.0);
+        assert_eq!(palette[1], abgr(0xffffffff));
+        assert_eq!(palette[2], abgr(0xffccffff));
+        assert_eq!(palette[3], abgr(0xff99ffff));
+        assert_eq!(palette[7], abgr(0xffffccff));
+        assert_eq!(palette[37], abgr(0xffffffcc));
+        assert_eq!(palette[215], abgr(0xff330000));
+        assert_eq!(palette[216], abgr(0xff0000ee));
+        assert_eq!(palette[226], abgr(0xff00ee00));
+        assert_eq!(palette[255], abgr(0xff111111));
+    }
+
+    fn chunk(id: &[u8; 4], content: &[u8], children: &[u8]) -> Vec<u8> {
+        let mut out = id.to_vec();
+        out.extend((content.len() as u32).to_le_bytes());
+        out.extend((children.len() as u32).to_le_bytes());
+        out.extend(content);
+        out.extend(children);
+        out
+    }
+
+    fn file(size: [u32; 3], voxels: &[[u8; 4]]) -> Vec<u8> {
+        let size = size
+            .iter()
+            .flat_map(|n| n.to_le_bytes())
+            .collect::<Vec<_>>();
+
+        let mut xyzi = (voxels.len() as u32).to_le_bytes().to_vec();
+        xyzi.extend(voxels.concat());
+
+        let children = [chunk(b"SIZE", &size, &[]), chunk(b"XYZI", &xyzi, &[])].concat();
+
+        let mut out = b"VOX ".to_vec();
+        out.extend(150u32.to_le_bytes());
+        out.extend(chunk(b"MAIN", &[], &children));
+        out
+    }
+
+    #[test]
+    fn voxels_are_turned_y_up() {
+        let models = parse(&file([2, 3, 4], &[[1, 0, 3, 2]])).unwrap();
+
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].size(), [2, 4, 3]);
+        // z up becomes y up, and y away from the viewer becomes depth
+        assert_eq!(models[0].get(1, 3, 2), default_palette()[2]);
+    }
+
+    #[test]
+    fn oversized_models_are_rejected() {
+        assert!(parse(&file([257, 1, 1], &[])).is_err());
+        assert!(parse(&file([u32::MAX; 3], &[])).is_err());
+        assert!(parse(&file([2, 2, 2], &[[2, 0, 0, 1]])).is_err());
+    }
+}
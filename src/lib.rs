@@ -0,0 +1,6 @@
+//! The signed distance field renderer, as a library so the benchmarks can
+//! link against the same code the binary runs.
+
+pub mod sdf;
+
+use cube_core::frame;
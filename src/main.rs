@@ -1,17 +1,16 @@
-#![allow(incomplete_features)]
-#![feature(specialization)]
-#![feature(vec_retain_mut)]
-#![feature(mixed_integer_ops)]
-#![feature(type_alias_impl_trait)]
-#![cfg_attr(feature = "visual", feature(adt_const_params))]
-
-mod animation;
 mod animations;
-mod frame;
+mod expr;
+mod font;
+mod import;
+mod particles;
 mod render;
-mod sdf;
+mod scene;
+mod volume;
 mod backends;
 
+use cube_core::{animation, frame};
+use cube_thing::sdf;
+
 #[cfg(feature = "visual")]
 mod visual;
 
@@ -26,15 +25,30 @@ fn main() {
     let animation = current_config();
 
     let backend = {
+        #[cfg(feature = "serial")]
+        {
+            use backends::serial::SerialBackend;
+            let path = std::env::var("CUBE_SERIAL").unwrap_or_else(|_| "/dev/ttyACM0".to_owned());
+            SerialBackend::new(path, 1_000_000)
+        }
+        #[cfg(not(feature = "serial"))]
         {
             use backends::null::NullBackend;
             NullBackend
         }
     };
 
-    let mut driver = crate::render::Driver::new(animation, backend);
+    let mut driver = crate::render::Driver::new(animation, backend).with_policy(
+        crate::render::ErrorPolicy::Retry {
+            attempts: 5,
+            backoff: std::time::Duration::from_millis(100),
+        },
+    );
 
     loop {
-        driver.step();
+        if let Err(e) = driver.step() {
+            eprintln!("backend failed: {}", e);
+            break;
+        }
     }
 }
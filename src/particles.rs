@@ -0,0 +1,394 @@
+//! A small particle engine: particles fly under gravity and drag until their
+//! lifetime runs out, changing colour as they age, and are added onto the
+//! frame so overlapping ones brighten each other.
+
+use std::ops::Range;
+
+use nalgebra::{Rotation3, Unit, Vector3};
+use palette::{Blend, LinSrgba, Mix};
+use rand::Rng;
+
+use crate::frame::Frame;
+
+/// How a particle's colour changes from birth to death.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColourOverLife {
+    pub start: LinSrgba,
+    pub end: LinSrgba,
+}
+
+impl ColourOverLife {
+    pub fn new(start: LinSrgba, end: LinSrgba) -> Self {
+        Self { start, end }
+    }
+
+    pub fn constant(colour: LinSrgba) -> Self {
+        Self::new(colour, colour)
+    }
+
+    /// Fades from `colour` to nothing.
+    pub fn fading(colour: LinSrgba) -> Self {
+        Self::new(
+            colour,
+            LinSrgba::new(colour.red, colour.green, colour.blue, 0.0),
+        )
+    }
+
+    /// The colour `t` of the way through a particle's life.
+    pub fn at(&self, t: f32) -> LinSrgba {
+        self.start.mix(&self.end, t.clamp(0.0, 1.0))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Particle {
+    pub position: Vector3<f32>,
+    pub velocity: Vector3<f32>,
+    /// Seconds since the particle was made.
+    pub age: f32,
+    /// Seconds it lives for.
+    pub lifetime: f32,
+    pub colour: ColourOverLife,
+}
+
+impl Particle {
+    pub fn new(
+        position: Vector3<f32>,
+        velocity: Vector3<f32>,
+        lifetime: f32,
+        colour: ColourOverLife,
+    ) -> Self {
+        Self {
+            position,
+            velocity,
+            age: 0.0,
+            lifetime,
+            colour,
+        }
+    }
+
+    pub fn alive(&self) -> bool {
+        self.age < self.lifetime
+    }
+
+    pub fn colour(&self) -> LinSrgba {
+        self.colour.at(self.age / self.lifetime)
+    }
+
+    /// Move the particle on by `dt` seconds. `drag` is the fraction of its
+    /// velocity lost per second.
+    pub fn update(&mut self, gravity: Vector3<f32>, drag: f32, dt: f32) {
+        self.velocity += gravity * dt;
+        self.velocity *= (1.0 - drag * dt).max(0.0);
+        self.position += self.velocity * dt;
+        self.age += dt;
+    }
+}
+
+fn between(rng: &mut impl Rng, range: &Range<f32>) -> f32 {
+    if range.is_empty() {
+        range.start
+    } else {
+        rng.gen_range(range.clone())
+    }
+}
+
+/// Makes particles at a point, heading out in a cone around `direction`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Emitter {
+    pub position: Vector3<f32>,
+    pub direction: Unit<Vector3<f32>>,
+    /// Angle in radians between `direction` and the edge of the cone, π
+    /// sends particles every way.
+    pub spread: f32,
+    pub speed: Range<f32>,
+    pub lifetime: Range<f32>,
+    /// Particles a second made by `emit`.
+    pub rate: f32,
+    pub colour: ColourOverLife,
+}
+
+impl Default for Emitter {
+    fn default() -> Self {
+        Self {
+            position: Vector3::zeros(),
+            direction: Vector3::y_axis(),
+            spread: std::f32::consts::PI,
+            speed: 1.0..2.0,
+            lifetime: 1.0..2.0,
+            rate: 10.0,
+            colour: ColourOverLife::fading(LinSrgba::new(1.0, 1.0, 1.0, 1.0)),
+        }
+    }
+}
+
+impl Emitter {
+    pub fn with_position(mut self, position: Vector3<f32>) -> Self {
+        self.position = position;
+        self
+    }
+
+    pub fn with_direction(mut self, direction: Unit<Vector3<f32>>, spread: f32) -> Self {
+        self.direction = direction;
+        self.spread = spread;
+        self
+    }
+
+    pub fn with_speed(mut self, speed: Range<f32>) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    pub fn with_lifetime(mut self, lifetime: Range<f32>) -> Self {
+        self.lifetime = lifetime;
+        self
+    }
+
+    pub fn with_rate(mut self, rate: f32) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    pub fn with_colour(mut self, colour: ColourOverLife) -> Self {
+        self.colour = colour;
+        self
+    }
+
+    fn particle(&self, rng: &mut impl Rng) -> Particle {
+        // uniform over the cap of the sphere within the cone, around y
+        let cos_theta = rng.gen_range(self.spread.cos().min(1.0)..=1.0);
+        let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+        let phi = rng.gen_range(0.0..std::f32::consts::TAU);
+        let local = Vector3::new(sin_theta * phi.cos(), cos_theta, sin_theta * phi.sin());
+
+        let to_direction = Rotation3::rotation_between(&Vector3::y(), &self.direction)
+            .unwrap_or_else(|| {
+                Rotation3::from_axis_angle(&Vector3::x_axis(), std::f32::consts::PI)
+            });
+
+        Particle::new(
+            self.position,
+            to_direction * local * between(rng, &self.speed),
+            between(rng, &self.lifetime),
+            self.colour,
+        )
+    }
+
+    /// Make `rate * dt` particles, rounded randomly so fractions of a
+    /// particle still add up over time. A negative rate makes none.
+    pub fn emit(&self, dt: f32, system: &mut ParticleSystem) {
+        let mut rng = rand::thread_rng();
+        let expected = (self.rate * dt).max(0.0);
+        let count = expected as usize + rng.gen_bool(expected.fract() as f64) as usize;
+
+        for _ in 0..count {
+            system.spawn(self.particle(&mut rng));
+        }
+    }
+
+    /// Make `count` particles at once.
+    pub fn burst(&self, count: usize, system: &mut ParticleSystem) {
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..count {
+            system.spawn(self.particle(&mut rng));
+        }
+    }
+}
+
+/// Add `colour` at `position`, shared between the eight voxels around it so
+/// particles move smoothly between them.
+pub fn splat(frame: &mut Frame, position: Vector3<f32>, colour: LinSrgba) {
+    let base = position.map(f32::floor);
+    let frac = position - base;
+    let colour = colour.into_premultiplied();
+
+    for corner in 0..8 {
+        let offset = Vector3::new(corner & 1, (corner >> 1) & 1, corner >> 2);
+        let voxel = base + offset.map(|o| o as f32);
+
+        if voxel
+            .iter()
+            .any(|&c| !(0.0..Frame::LAYERS as f32).contains(&c))
+        {
+            continue;
+        }
+
+        let weight = (0..3)
+            .map(|i| {
+                if offset[i] == 1 {
+                    frac[i]
+                } else {
+                    1.0 - frac[i]
+                }
+            })
+            .product::<f32>();
+
+        let (x, y, z) = (voxel.x as usize, voxel.y as usize, voxel.z as usize);
+        let mut sum = frame.get(x, y, z).into_premultiplied() + colour * weight;
+
+        // light adds up, but a voxel can't be more than opaque
+        sum.alpha = sum.alpha.min(1.0);
+        frame.set(x, y, z, LinSrgba::from_premultiplied(sum));
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ParticleSystem {
+    pub gravity: Vector3<f32>,
+    /// Fraction of velocity lost per second.
+    pub drag: f32,
+    particles: Vec<Particle>,
+}
+
+impl Default for ParticleSystem {
+    fn default() -> Self {
+        Self {
+            gravity: Vector3::new(0.0, -9.81, 0.0),
+            drag: 0.0,
+            particles: Vec::new(),
+        }
+    }
+}
+
+impl ParticleSystem {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_gravity(mut self, gravity: Vector3<f32>) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    pub fn with_drag(mut self, drag: f32) -> Self {
+        self.drag = drag;
+        self
+    }
+
+    pub fn spawn(&mut self, particle: Particle) {
+        self.particles.push(particle);
+    }
+
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    pub fn len(&self) -> usize {
+        self.particles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.particles.clear();
+    }
+
+    /// Move every particle on by `dt` seconds and drop the ones which have
+    /// died.
+    pub fn step(&mut self, dt: f32) {
+        for particle in &mut self.particles {
+            particle.update(self.gravity, self.drag, dt);
+        }
+
+        self.particles.retain(Particle::alive);
+    }
+
+    /// Add every particle onto `frame`, which isn't cleared first.
+    pub fn render(&self, frame: &mut Frame) {
+        for particle in &self.particles {
+            splat(frame, particle.position, particle.colour());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negative_rates_emit_nothing() {
+        let mut system = ParticleSystem::new();
+
+        Emitter::default().with_rate(-2.5).emit(1.0, &mut system);
+        Emitter::default().emit(-0.3, &mut system);
+        assert!(system.is_empty());
+    }
+
+    #[test]
+    fn emits_rate_times_dt() {
+        let mut system = ParticleSystem::new();
+
+        Emitter::default().with_rate(10.0).emit(0.5, &mut system);
+        assert_eq!(system.len(), 5);
+
+        // fractions are rounded up or down at random, but add up
+        let emitter = Emitter::default().with_rate(2.5);
+        system.clear();
+        for _ in 0..1000 {
+            let before = system.len();
+            emitter.emit(1.0, &mut system);
+            assert!((2..=3).contains(&(system.len() - before)));
+        }
+        assert!((2300..=2700).contains(&system.len()), "{}", system.len());
+
+        system.clear();
+        emitter.burst(7, &mut system);
+        assert_eq!(system.len(), 7);
+    }
+
+    #[test]
+    fn particles_leave_along_the_cone() {
+        let mut system = ParticleSystem::new();
+        let emitter = Emitter::default()
+            .with_position(Vector3::new(4.0, 4.0, 4.0))
+            .with_direction(Vector3::x_axis(), 0.0)
+            .with_speed(2.0..3.0);
+        emitter.burst(20, &mut system);
+
+        for particle in system.particles() {
+            let v = particle.velocity;
+            assert!(v.x >= 2.0 && v.x < 3.0, "{:?}", v);
+            assert!(v.yz().norm() < 1e-3, "{:?}", v);
+        }
+    }
+
+    #[test]
+    fn particles_die_of_old_age() {
+        let mut system = ParticleSystem::new();
+        Emitter::default()
+            .with_lifetime(1.0..1.0)
+            .burst(3, &mut system);
+        Emitter::default()
+            .with_lifetime(2.0..2.0)
+            .burst(2, &mut system);
+
+        system.step(0.6);
+        assert_eq!(system.len(), 5);
+        system.step(0.6);
+        assert_eq!(system.len(), 2);
+        system.step(1.0);
+        assert!(system.is_empty());
+    }
+
+    #[test]
+    fn splats_share_between_voxels() {
+        let mut frame = Frame::new();
+        let white = LinSrgba::new(1.0, 1.0, 1.0, 1.0);
+
+        splat(&mut frame, Vector3::new(2.0, 3.0, 4.0), white);
+        assert_eq!(frame.get(2, 3, 4), white);
+
+        frame.zero();
+        splat(&mut frame, Vector3::new(2.5, 3.0, 4.0), white);
+        assert_eq!(frame.get(2, 3, 4).alpha, 0.5);
+        assert_eq!(frame.get(3, 3, 4).alpha, 0.5);
+
+        // partly off the edge, the rest is dropped
+        frame.zero();
+        splat(&mut frame, Vector3::new(-0.5, 0.0, 0.0), white);
+        assert_eq!(frame.get(0, 0, 0).alpha, 0.5);
+    }
+}
@@ -1,14 +1,100 @@
-use crate::{animation::MaybeTerminatingAnimation, backends::backend::Backend, frame::Frame};
+use std::time::Duration;
 
-pub struct Driver<Anim, Backend> {
-    animation: Anim,
-    backend: Backend,
+use crate::{
+    animation::Animation,
+    backends::backend::{Backend, BackendError},
+    frame::Frame,
+};
+
+/// The longest `ErrorPolicy::Retry` waits between attempts.
+pub const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// What the driver should do when the backend fails to display a frame.
+#[derive(Clone, Copy, Debug, Default)]
+pub enum ErrorPolicy {
+    /// Return the error from `step`.
+    #[default]
+    Abort,
+    /// Drop the frame and carry on with the next one.
+    Skip,
+    /// Reopen the backend and try the frame again, doubling the delay after
+    /// each attempt up to `MAX_BACKOFF`. The last error is returned once the attempts run out.
+    Retry { attempts: usize, backoff: Duration },
+}
+
+fn present<B: Backend + ?Sized>(
+    backend: &mut B,
+    frame: &Frame,
+    policy: ErrorPolicy,
+) -> Result<(), BackendError> {
+    let err = match backend.display_frame(frame).and_then(|_| backend.flush()) {
+        Ok(()) => return Ok(()),
+        Err(e) => e,
+    };
+
+    match policy {
+        ErrorPolicy::Abort => Err(err),
+        ErrorPolicy::Skip => {
+            dbg!("skipping frame", &err);
+            Ok(())
+        }
+        ErrorPolicy::Retry { attempts, backoff } => {
+            let mut err = err;
+            let mut delay = backoff.min(MAX_BACKOFF);
+
+            for attempt in 0..attempts {
+                dbg!("retrying backend", attempt, &err);
+                std::thread::sleep(delay);
+                delay = delay.saturating_mul(2).min(MAX_BACKOFF);
+
+                let result = backend
+                    .close()
+                    .and_then(|_| backend.open())
+                    .and_then(|_| backend.display_frame(frame))
+                    .and_then(|_| backend.flush());
+
+                match result {
+                    Ok(()) => return Ok(()),
+                    Err(e) => err = e,
+                }
+            }
+
+            Err(err)
+        }
+    }
+}
+
+/// Blank and release the backend, if it was ever opened.
+fn shutdown<B: Backend + ?Sized>(
+    backend: &mut B,
+    opened: &mut bool,
+    blank_on_exit: bool,
+) -> Result<(), BackendError> {
+    if !std::mem::replace(opened, false) {
+        return Ok(());
+    }
+
+    let blanked = if blank_on_exit {
+        backend.blank()
+    } else {
+        Ok(())
+    };
+
+    backend.close().and(blanked)
+}
+
+pub struct Driver<A, B: Backend> {
+    animation: A,
+    backend: B,
     frame: Frame,
+    policy: ErrorPolicy,
+    blank_on_exit: bool,
+    opened: bool,
 }
 
 impl<A, B> Driver<A, B>
 where
-    A: MaybeTerminatingAnimation + Send + Sync,
+    A: Animation + Send + Sync,
     B: Backend + Send + Sync,
 {
     pub fn new(animation: A, backend: B) -> Self {
@@ -18,20 +104,44 @@ where
             animation,
             backend,
             frame,
+            policy: ErrorPolicy::default(),
+            blank_on_exit: true,
+            opened: false,
         }
     }
 
+    pub fn with_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn with_blank_on_exit(mut self, blank_on_exit: bool) -> Self {
+        self.blank_on_exit = blank_on_exit;
+        self
+    }
+
     pub fn reset(&mut self) {
         self.animation.reset();
     }
 
-    pub fn step(&mut self) {
-        if self.animation.maybe_ended() {
+    pub fn step(&mut self) -> Result<(), BackendError> {
+        if !self.opened {
+            self.backend.open()?;
+            self.opened = true;
+        }
+
+        if self.animation.ended() {
             self.animation.reset();
         }
 
         self.animation.next_frame(&mut self.frame);
-        self.backend.display_frame(&self.frame);
+        present(&mut self.backend, &self.frame, self.policy)
+    }
+
+    /// Blank and close the backend, this also happens when the driver is
+    /// dropped but any errors are lost then.
+    pub fn close(&mut self) -> Result<(), BackendError> {
+        shutdown(&mut self.backend, &mut self.opened, self.blank_on_exit)
     }
 
     pub fn frame(&self) -> &Frame {
@@ -39,16 +149,25 @@ where
     }
 }
 
+impl<A, B: Backend> Drop for Driver<A, B> {
+    fn drop(&mut self) {
+        let _ = shutdown(&mut self.backend, &mut self.opened, self.blank_on_exit);
+    }
+}
+
 pub struct DynDriver {
-    animation: Box<dyn MaybeTerminatingAnimation + Send + Sync + 'static>,
+    animation: Box<dyn Animation + Send + Sync + 'static>,
     backend: Box<dyn Backend + Send + Sync + 'static>,
     frame: Frame,
+    policy: ErrorPolicy,
+    blank_on_exit: bool,
+    opened: bool,
 }
 
 impl DynDriver {
     pub fn new<A, B>(animation: A, backend: B) -> Self
     where
-        A: MaybeTerminatingAnimation + Send + Sync + 'static,
+        A: Animation + Send + Sync + 'static,
         B: Backend + Send + Sync + 'static,
     {
         let frame = Frame::new();
@@ -59,23 +178,139 @@ impl DynDriver {
             animation,
             backend,
             frame,
+            policy: ErrorPolicy::default(),
+            blank_on_exit: true,
+            opened: false,
         }
     }
 
+    pub fn with_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn with_blank_on_exit(mut self, blank_on_exit: bool) -> Self {
+        self.blank_on_exit = blank_on_exit;
+        self
+    }
+
     pub fn reset(&mut self) {
         self.animation.reset();
     }
 
-    pub fn step(&mut self) {
-        if self.animation.maybe_ended() {
+    pub fn step(&mut self) -> Result<(), BackendError> {
+        if !self.opened {
+            self.backend.open()?;
+            self.opened = true;
+        }
+
+        if self.animation.ended() {
             self.animation.reset();
         }
 
         self.animation.next_frame(&mut self.frame);
-        self.backend.display_frame(&self.frame);
+        present(&mut *self.backend, &self.frame, self.policy)
+    }
+
+    /// Blank and close the backend, this also happens when the driver is
+    /// dropped but any errors are lost then.
+    pub fn close(&mut self) -> Result<(), BackendError> {
+        shutdown(&mut *self.backend, &mut self.opened, self.blank_on_exit)
     }
 
     pub fn frame(&self) -> &Frame {
         &self.frame
     }
 }
+
+impl Drop for DynDriver {
+    fn drop(&mut self) {
+        let _ = shutdown(&mut *self.backend, &mut self.opened, self.blank_on_exit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fails its first `failures` frames.
+    #[derive(Default)]
+    struct Flaky {
+        failures: usize,
+        opens: usize,
+        frames: usize,
+    }
+
+    impl Backend for Flaky {
+        fn open(&mut self) -> Result<(), BackendError> {
+            self.opens += 1;
+            Ok(())
+        }
+
+        fn display_frame(&mut self, _frame: &Frame) -> Result<(), BackendError> {
+            if self.failures > 0 {
+                self.failures -= 1;
+                return Err(BackendError::Disconnected);
+            }
+
+            self.frames += 1;
+            Ok(())
+        }
+    }
+
+    fn retry(attempts: usize) -> ErrorPolicy {
+        ErrorPolicy::Retry {
+            attempts,
+            backoff: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn abort() {
+        let mut backend = Flaky {
+            failures: 1,
+            ..Default::default()
+        };
+
+        let result = present(&mut backend, &Frame::new(), ErrorPolicy::Abort);
+        assert!(matches!(result, Err(BackendError::Disconnected)));
+        assert_eq!(backend.frames, 0);
+    }
+
+    #[test]
+    fn skip() {
+        let mut backend = Flaky {
+            failures: 1,
+            ..Default::default()
+        };
+
+        present(&mut backend, &Frame::new(), ErrorPolicy::Skip).unwrap();
+        assert_eq!((backend.opens, backend.frames), (0, 0));
+
+        present(&mut backend, &Frame::new(), ErrorPolicy::Skip).unwrap();
+        assert_eq!(backend.frames, 1);
+    }
+
+    #[test]
+    fn retry_until_it_works() {
+        let mut backend = Flaky {
+            failures: 2,
+            ..Default::default()
+        };
+
+        present(&mut backend, &Frame::new(), retry(3)).unwrap();
+        assert_eq!((backend.opens, backend.frames), (2, 1));
+    }
+
+    #[test]
+    fn retry_gives_up() {
+        let mut backend = Flaky {
+            failures: 10,
+            ..Default::default()
+        };
+
+        let result = present(&mut backend, &Frame::new(), retry(3));
+        assert!(matches!(result, Err(BackendError::Disconnected)));
+        assert_eq!((backend.opens, backend.frames), (3, 0));
+    }
+}
@@ -0,0 +1,333 @@
+use nalgebra::{Isometry3, Point3, UnitQuaternion, Vector3};
+use palette::{Blend, LinSrgba, Mix};
+use sdfu::{ops::HardMin, SDF};
+
+use crate::sdf::{Aabb, CsgOp, Evaluate, Intersection, Smooth, Subtraction, Union, Xor};
+
+/// An object safe `SDF`, so shapes of different types can share a scene.
+pub trait DynSdf: Send + Sync {
+    fn dist(&self, p: Vector3<f32>) -> f32;
+
+    fn colour(&self, p: Vector3<f32>) -> LinSrgba;
+}
+
+impl<S: SDF<f32, Vector3<f32>> + Send + Sync> DynSdf for S {
+    #[inline]
+    fn dist(&self, p: Vector3<f32>) -> f32 {
+        SDF::dist(self, p)
+    }
+
+    #[inline]
+    fn colour(&self, p: Vector3<f32>) -> LinSrgba {
+        SDF::colour(self, p)
+    }
+}
+
+/// A box centred on the origin.
+#[derive(Clone, Copy, Debug)]
+pub struct Cuboid {
+    half_extents: Vector3<f32>,
+    colour: LinSrgba,
+}
+
+impl Cuboid {
+    pub fn new(half_extents: Vector3<f32>, colour: LinSrgba) -> Self {
+        Self {
+            half_extents,
+            colour,
+        }
+    }
+}
+
+impl SDF<f32, Vector3<f32>> for Cuboid {
+    fn dist(&self, p: Vector3<f32>) -> f32 {
+        let q = p.abs() - self.half_extents;
+
+        q.sup(&Vector3::zeros()).norm() + q.max().min(0.0)
+    }
+
+    fn colour(&self, _p: Vector3<f32>) -> LinSrgba {
+        self.colour
+    }
+}
+
+/// A ring lying flat in the xz plane, centred on the origin.
+#[derive(Clone, Copy, Debug)]
+pub struct Torus {
+    radius: f32,
+    thickness: f32,
+    colour: LinSrgba,
+}
+
+impl Torus {
+    pub fn new(radius: f32, thickness: f32, colour: LinSrgba) -> Self {
+        Self {
+            radius,
+            thickness,
+            colour,
+        }
+    }
+}
+
+impl SDF<f32, Vector3<f32>> for Torus {
+    fn dist(&self, p: Vector3<f32>) -> f32 {
+        let ring = p.xz().norm() - self.radius;
+
+        (ring * ring + p.y * p.y).sqrt() - self.thickness
+    }
+
+    fn colour(&self, _p: Vector3<f32>) -> LinSrgba {
+        self.colour
+    }
+}
+
+/// How a node is combined with the nodes before it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BlendOp {
+    Union,
+    SmoothUnion(f32),
+    Subtract,
+    SmoothSubtract(f32),
+    Intersect,
+    SmoothIntersect(f32),
+    Xor,
+}
+
+impl BlendOp {
+    /// Returns the combined distance and how far to mix from `a`'s colour
+    /// towards `b`'s.
+    fn combine(self, a: f32, b: f32) -> (f32, f32) {
+        let hard = HardMin::default();
+
+        match self {
+            BlendOp::Union => Union::combine(&hard, a, b),
+            BlendOp::SmoothUnion(r) => Union::combine(&Smooth::poly(r), a, b),
+            BlendOp::Subtract => Subtraction::combine(&hard, a, b),
+            BlendOp::SmoothSubtract(r) => Subtraction::combine(&Smooth::poly(r), a, b),
+            BlendOp::Intersect => Intersection::combine(&hard, a, b),
+            BlendOp::SmoothIntersect(r) => Intersection::combine(&Smooth::poly(r), a, b),
+            BlendOp::Xor => Xor::combine(&hard, a, b),
+        }
+    }
+
+    /// How far past the node's own surface the result can swell.
+    fn reach(self) -> f32 {
+        match self {
+            BlendOp::SmoothUnion(r) | BlendOp::SmoothSubtract(r) | BlendOp::SmoothIntersect(r) => r,
+            BlendOp::Union | BlendOp::Subtract | BlendOp::Intersect | BlendOp::Xor => 0.0,
+        }
+    }
+
+    /// Whether the op only ever takes away from the nodes before it.
+    fn removes(self) -> bool {
+        matches!(
+            self,
+            BlendOp::Subtract
+                | BlendOp::SmoothSubtract(_)
+                | BlendOp::Intersect
+                | BlendOp::SmoothIntersect(_)
+        )
+    }
+}
+
+pub struct Node {
+    shape: Box<dyn DynSdf>,
+    /// A box around the shape before it's moved, if one is known.
+    local_bounds: Option<Aabb>,
+    transform: Isometry3<f32>,
+    scale: f32,
+    colour: Option<LinSrgba>,
+    op: BlendOp,
+}
+
+impl Node {
+    pub fn new<S: DynSdf + 'static>(shape: S) -> Self {
+        Self {
+            shape: Box::new(shape),
+            local_bounds: None,
+            transform: Isometry3::identity(),
+            scale: 1.0,
+            colour: None,
+            op: BlendOp::Union,
+        }
+    }
+
+    pub fn sphere(radius: f32, colour: LinSrgba) -> Self {
+        Self::new(sdfu::Sphere::new(radius, colour))
+            .with_local_bounds(Aabb::sphere(Vector3::zeros(), radius))
+    }
+
+    pub fn cuboid(half_extents: Vector3<f32>, colour: LinSrgba) -> Self {
+        Self::new(Cuboid::new(half_extents, colour))
+            .with_local_bounds(Aabb::new(-half_extents, half_extents))
+    }
+
+    pub fn line(start: Vector3<f32>, end: Vector3<f32>, thickness: f32, colour: LinSrgba) -> Self {
+        Self::new(sdfu::Line::new(start, end, thickness, colour))
+            .with_local_bounds(Aabb::new(start.inf(&end), start.sup(&end)).expand(thickness))
+    }
+
+    pub fn torus(radius: f32, thickness: f32, colour: LinSrgba) -> Self {
+        let outer = radius + thickness;
+
+        Self::new(Torus::new(radius, thickness, colour)).with_local_bounds(Aabb::new(
+            Vector3::new(-outer, -thickness, -outer),
+            Vector3::new(outer, thickness, outer),
+        ))
+    }
+
+    fn with_local_bounds(mut self, bounds: Aabb) -> Self {
+        self.local_bounds = Some(bounds);
+        self
+    }
+
+    pub fn at(mut self, translation: Vector3<f32>) -> Self {
+        self.transform.translation.vector = translation;
+        self
+    }
+
+    pub fn rotated(mut self, rotation: UnitQuaternion<f32>) -> Self {
+        self.transform.rotation = rotation;
+        self
+    }
+
+    pub fn scaled(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Paint the whole node `colour`, ignoring the shape's own colour.
+    pub fn coloured(mut self, colour: LinSrgba) -> Self {
+        self.colour = Some(colour);
+        self
+    }
+
+    pub fn with_op(mut self, op: BlendOp) -> Self {
+        self.op = op;
+        self
+    }
+
+    pub fn transform_mut(&mut self) -> &mut Isometry3<f32> {
+        &mut self.transform
+    }
+
+    fn local(&self, p: Vector3<f32>) -> Vector3<f32> {
+        self.transform
+            .inverse_transform_point(&Point3::from(p))
+            .coords
+            / self.scale
+    }
+
+    fn eval(&self, p: Vector3<f32>) -> (f32, LinSrgba) {
+        let local = self.local(p);
+        let colour = self.colour.unwrap_or_else(|| self.shape.colour(local));
+
+        (self.shape.dist(local) * self.scale, colour)
+    }
+
+    /// A box around the moved shape, fitted round the corners of the local
+    /// one.
+    fn bounds(&self) -> Option<Aabb> {
+        let Aabb { min, max } = self.local_bounds?;
+
+        let corners = (0..8).map(|i| {
+            let pick = |axis: usize| {
+                if i & (1 << axis) == 0 {
+                    min[axis]
+                } else {
+                    max[axis]
+                }
+            };
+            let corner = Point3::new(pick(0), pick(1), pick(2)) * self.scale;
+
+            self.transform.transform_point(&corner).coords
+        });
+
+        corners.map(|c| Aabb::new(c, c)).reduce(|a, b| a.union(&b))
+    }
+}
+
+/// Shapes of any type, each with its own transform, colour and way of being
+/// combined with the nodes before it. Render it with `render_sdf(&scene, ..)`.
+#[derive(Default)]
+pub struct Scene {
+    nodes: Vec<Node>,
+}
+
+impl Scene {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, node: Node) -> Self {
+        self.push(node);
+        self
+    }
+
+    pub fn push(&mut self, node: Node) {
+        self.nodes.push(node);
+    }
+
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn nodes_mut(&mut self) -> &mut [Node] {
+        &mut self.nodes
+    }
+}
+
+impl SDF<f32, Vector3<f32>> for &Scene {
+    fn dist(&self, p: Vector3<f32>) -> f32 {
+        self.eval(p).0
+    }
+
+    fn colour(&self, p: Vector3<f32>) -> LinSrgba {
+        self.eval(p).1
+    }
+}
+
+impl Evaluate for Scene {
+    fn eval(&self, p: Vector3<f32>) -> (f32, LinSrgba) {
+        let mut nodes = self.nodes.iter();
+
+        let (mut dist, colour) = match nodes.next() {
+            Some(node) => node.eval(p),
+            None => return (f32::INFINITY, LinSrgba::new(0.0, 0.0, 0.0, 0.0)),
+        };
+        let mut colour = colour.into_premultiplied();
+
+        for node in nodes {
+            let (d, c) = node.eval(p);
+            let (m, blend) = node.op.combine(dist, d);
+
+            dist = m;
+            if blend > 0.0 {
+                colour = colour.mix(&c.into_premultiplied(), blend);
+            }
+        }
+
+        (dist, LinSrgba::from_premultiplied(colour))
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        let mut nodes = self.nodes.iter();
+        let first = nodes.next()?;
+
+        nodes.try_fold(first.bounds()?, |bounds, node| {
+            if node.op.removes() {
+                return Some(bounds);
+            }
+
+            Some(bounds.union(&node.bounds()?.expand(node.op.reach())))
+        })
+    }
+}
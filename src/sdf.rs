@@ -1,57 +1,479 @@
-use std::ops::{Div, Sub};
-
-use num::One;
-use palette::{Blend, LinSrgba};
+use nalgebra::Vector3;
+use num::Float;
 use palette::Mix;
+use palette::{Blend, LinSrgba};
 use sdfu::{
-    ops::{HardMin, MinFunction},
-    SDF,
+    mods::Translate,
+    ops::{ExponentialSmoothMin, HardMin, MinFunction, PolySmoothMin, PowerSmoothMin},
+    Line, Sphere, SDF,
 };
 
 use crate::frame::Frame;
 
-pub fn render_sdf<V, S>(sdf: S, frame: &mut Frame)
+/// Where in each voxel the SDF is sampled.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Sampling {
+    /// Only the voxel centre.
+    Centre,
+    /// An n×n×n grid spread evenly through the voxel.
+    Grid(u8),
+    /// An n×n×n grid with each sample moved randomly within its cell. The
+    /// offsets are the same every frame so still scenes don't shimmer.
+    Jittered(u8),
+}
+
+impl Sampling {
+    /// Offsets from the voxel centre, each axis in `-0.5..0.5`.
+    fn offsets(self) -> Vec<[f32; 3]> {
+        let (n, jittered) = match self {
+            Sampling::Centre => return vec![[0.0; 3]],
+            Sampling::Grid(n) => (n.max(1) as u32, false),
+            Sampling::Jittered(n) => (n.max(1) as u32, true),
+        };
+
+        let mut offsets = Vec::with_capacity((n * n * n) as usize);
+
+        for i in 0..n * n * n {
+            let cell = [i % n, (i / n) % n, i / (n * n)];
+
+            offsets.push([0, 1, 2].map(|axis| {
+                let within = if jittered {
+                    jitter(i * 3 + axis as u32)
+                } else {
+                    0.5
+                };
+
+                (cell[axis] as f32 + within) / n as f32 - 0.5
+            }));
+        }
+
+        offsets
+    }
+}
+
+/// A fixed pseudo random number in `0.0..1.0` for each `i`.
+fn jitter(i: u32) -> f32 {
+    let mut h = i.wrapping_mul(0x9E37_79B9) ^ 0x85EB_CA6B;
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x7FEB_352D);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x846C_A68B);
+    h ^= h >> 16;
+
+    (h >> 8) as f32 / (1 << 24) as f32
+}
+
+/// How a shape's alpha fades with distance from its surface.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Falloff {
+    /// Opaque up to `threshold`, then `1 / dist^power`. Note this goes above
+    /// 1 between `threshold` and 1, which makes the preview glow.
+    InversePower { threshold: f32, power: i32 },
+    /// Opaque before `start`, fading smoothly to nothing at `end`. A hard
+    /// step at `start` if `end` isn't past it.
+    Smoothstep { start: f32, end: f32 },
+    /// Opaque inside, a gaussian of width `sigma` outside. A hard edge if
+    /// `sigma` is zero.
+    Gaussian { sigma: f32 },
+    /// Opaque inside, `e^(-rate * dist)` outside.
+    Exponential { rate: f32 },
+    /// Opaque up to `threshold`, nothing past it.
+    Hard { threshold: f32 },
+}
+
+impl Falloff {
+    pub fn alpha(&self, dist: f32) -> f32 {
+        match *self {
+            Falloff::InversePower { threshold, power } => {
+                if dist > threshold {
+                    1.0 / dist.powi(power)
+                } else {
+                    1.0
+                }
+            }
+            Falloff::Smoothstep { start, end } if end <= start => {
+                if dist <= start {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Falloff::Smoothstep { start, end } => {
+                let t = ((dist - start) / (end - start)).clamp(0.0, 1.0);
+                1.0 - t * t * (3.0 - 2.0 * t)
+            }
+            Falloff::Gaussian { sigma } => {
+                let d = dist.max(0.0);
+                let sigma = sigma.max(f32::EPSILON);
+                (-(d * d) / (2.0 * sigma * sigma)).exp()
+            }
+            Falloff::Exponential { rate } => (-rate * dist.max(0.0)).exp(),
+            Falloff::Hard { threshold } => {
+                if dist <= threshold {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+/// How the samples of a voxel become its alpha.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AlphaMode {
+    /// Average the falloff of every sample.
+    Falloff,
+    /// The fraction of samples within `radius` of the surface, ignoring the
+    /// falloff. A radius of zero only counts samples inside the shape.
+    Coverage { radius: f32 },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RenderOptions {
+    pub sampling: Sampling,
+    pub falloff: Falloff,
+    pub alpha: AlphaMode,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            sampling: Sampling::Centre,
+            falloff: Falloff::InversePower {
+                threshold: 0.2,
+                power: 5,
+            },
+            alpha: AlphaMode::Falloff,
+        }
+    }
+}
+
+impl RenderOptions {
+    /// Supersampled with a soft edge, for thin or small shapes which
+    /// otherwise flicker as they move between voxels.
+    pub fn antialiased() -> Self {
+        Self {
+            sampling: Sampling::Jittered(3),
+            falloff: Falloff::Smoothstep {
+                start: 0.0,
+                end: 0.8,
+            },
+            alpha: AlphaMode::Falloff,
+        }
+    }
+
+    pub fn with_sampling(mut self, sampling: Sampling) -> Self {
+        self.sampling = sampling;
+        self
+    }
+
+    pub fn with_falloff(mut self, falloff: Falloff) -> Self {
+        self.falloff = falloff;
+        self
+    }
+
+    pub fn with_alpha(mut self, alpha: AlphaMode) -> Self {
+        self.alpha = alpha;
+        self
+    }
+}
+
+/// An axis aligned box which a shape fits entirely inside.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Vector3<f32>,
+    pub max: Vector3<f32>,
+}
+
+impl Aabb {
+    pub fn new(min: Vector3<f32>, max: Vector3<f32>) -> Self {
+        Self { min, max }
+    }
+
+    pub fn sphere(centre: Vector3<f32>, radius: f32) -> Self {
+        let r = Vector3::repeat(radius);
+
+        Self::new(centre - r, centre + r)
+    }
+
+    pub fn union(&self, other: &Aabb) -> Self {
+        Self::new(self.min.inf(&other.min), self.max.sup(&other.max))
+    }
+
+    pub fn expand(&self, by: f32) -> Self {
+        let by = Vector3::repeat(by);
+
+        Self::new(self.min - by, self.max + by)
+    }
+
+    /// How far `p` is from the box, which is never more than how far it is
+    /// from the shape inside. Zero inside the box.
+    pub fn dist(&self, p: Vector3<f32>) -> f32 {
+        (self.min - p)
+            .sup(&(p - self.max))
+            .sup(&Vector3::zeros())
+            .norm()
+    }
+}
+
+/// What the renderer needs from a shape. Unlike `SDF` the distance and colour
+/// come out of one call, so combinators only visit their children once.
+pub trait Evaluate {
+    fn eval(&self, p: Vector3<f32>) -> (f32, LinSrgba);
+
+    /// A box the shape fits inside, if one is known. Shapes with bounds are
+    /// skipped wherever they can't change the result.
+    fn bounds(&self) -> Option<Aabb> {
+        None
+    }
+}
+
+impl<E: Evaluate + ?Sized> Evaluate for &E {
+    #[inline]
+    fn eval(&self, p: Vector3<f32>) -> (f32, LinSrgba) {
+        (**self).eval(p)
+    }
+
+    #[inline]
+    fn bounds(&self) -> Option<Aabb> {
+        (**self).bounds()
+    }
+}
+
+impl Evaluate for Sphere<f32> {
+    #[inline]
+    fn eval(&self, p: Vector3<f32>) -> (f32, LinSrgba) {
+        (self.dist(p), self.colour(p))
+    }
+}
+
+impl Evaluate for Line<f32, Vector3<f32>> {
+    #[inline]
+    fn eval(&self, p: Vector3<f32>) -> (f32, LinSrgba) {
+        (self.dist(p), self.colour(p))
+    }
+}
+
+impl<S> Evaluate for Translate<Vector3<f32>, S>
 where
-    V: sdfu::mathtypes::Vec3<f32>,
-    S: SDF<f32, V>,
+    Translate<Vector3<f32>, S>: SDF<f32, Vector3<f32>>,
 {
-    for (x, y, z, pix) in frame.pixels_mut() {
-        let pos = V::new(x as f32, y as f32, z as f32);
-        let dist = sdf.dist(pos);
-        let mut colour = sdf.colour(pos);
-
-        if dist > 0.2 {
-            let alpha = 1.0 / (dist).powi(5);
-            colour.alpha *= alpha;
-        }
+    #[inline]
+    fn eval(&self, p: Vector3<f32>) -> (f32, LinSrgba) {
+        (self.dist(p), self.colour(p))
+    }
+}
 
-        *pix = colour;
+/// A shape along with a box it fits inside, sdfu's primitives don't expose
+/// enough to work one out for themselves.
+#[derive(Clone, Copy, Debug)]
+pub struct Bounded<S> {
+    inner: S,
+    bounds: Aabb,
+}
+
+impl<S> Bounded<S> {
+    pub fn new(sdf: S, bounds: Aabb) -> Self {
+        Self { inner: sdf, bounds }
     }
 }
 
+impl Bounded<Translate<Vector3<f32>, Sphere<f32>>> {
+    pub fn sphere(centre: Vector3<f32>, radius: f32, colour: LinSrgba) -> Self {
+        Self::new(
+            Sphere::new(radius, colour).translate(centre),
+            Aabb::sphere(centre, radius),
+        )
+    }
+}
+
+impl<S: SDF<f32, Vector3<f32>>> SDF<f32, Vector3<f32>> for Bounded<S> {
+    #[inline]
+    fn dist(&self, p: Vector3<f32>) -> f32 {
+        self.inner.dist(p)
+    }
+
+    #[inline]
+    fn colour(&self, p: Vector3<f32>) -> LinSrgba {
+        self.inner.colour(p)
+    }
+}
+
+impl<S: Evaluate> Evaluate for Bounded<S> {
+    #[inline]
+    fn eval(&self, p: Vector3<f32>) -> (f32, LinSrgba) {
+        self.inner.eval(p)
+    }
+
+    #[inline]
+    fn bounds(&self) -> Option<Aabb> {
+        Some(self.bounds)
+    }
+}
+
+pub fn render_sdf<S: Evaluate + Sync>(sdf: S, frame: &mut Frame) {
+    render_sdf_with(sdf, frame, &RenderOptions::default());
+}
+
+/// Renders `sdf` into `frame`, spread across threads when built with the
+/// `parallel` feature.
+pub fn render_sdf_with<S: Evaluate + Sync>(sdf: S, frame: &mut Frame, options: &RenderOptions) {
+    let offsets = options.sampling.offsets();
+    let bounds = sdf.bounds();
+
+    frame.par_for_each(|x, y, z, pix| {
+        let centre = [x as f32, y as f32, z as f32];
+
+        *pix = match bounds {
+            Some(bounds) if outside(&bounds, centre, options) => LinSrgba::new(0.0, 0.0, 0.0, 0.0),
+            _ => sample_voxel(&sdf, centre, &offsets, options),
+        };
+    });
+}
+
+/// Below this an alpha doesn't survive being sent out as a byte.
+const INVISIBLE: f32 = 1.0 / 255.0;
+
+/// Whether the voxel at `centre` is far enough from `bounds` that it would be
+/// left invisible. Most falloffs never quite reach zero, so this is only
+/// faint enough to round to nothing, not fully transparent.
+fn outside(bounds: &Aabb, centre: [f32; 3], options: &RenderOptions) -> bool {
+    // samples are never further than half a voxel diagonal from the centre
+    let dist = bounds.dist(Vector3::from(centre)) - 3.0f32.sqrt() / 2.0;
+
+    match options.alpha {
+        AlphaMode::Falloff => options.falloff.alpha(dist.max(0.0)) < INVISIBLE,
+        AlphaMode::Coverage { radius } => dist > radius,
+    }
+}
+
+fn sample_voxel<S: Evaluate>(
+    sdf: &S,
+    centre: [f32; 3],
+    offsets: &[[f32; 3]],
+    options: &RenderOptions,
+) -> LinSrgba {
+    if let [offset] = offsets {
+        let pos = Vector3::new(
+            centre[0] + offset[0],
+            centre[1] + offset[1],
+            centre[2] + offset[2],
+        );
+        let (dist, mut colour) = sdf.eval(pos);
+
+        colour.alpha *= match options.alpha {
+            AlphaMode::Falloff => options.falloff.alpha(dist),
+            AlphaMode::Coverage { radius } if dist <= radius => 1.0,
+            AlphaMode::Coverage { .. } => 0.0,
+        };
+
+        return colour;
+    }
+
+    // uncovered samples count as transparent, so in both modes the voxel is
+    // the plain average of its premultiplied samples
+    let mut colour = LinSrgba::new(0.0, 0.0, 0.0, 0.0).into_premultiplied();
+
+    for offset in offsets {
+        let pos = Vector3::new(
+            centre[0] + offset[0],
+            centre[1] + offset[1],
+            centre[2] + offset[2],
+        );
+        let (dist, mut sample) = sdf.eval(pos);
+
+        let weight = match options.alpha {
+            // a single sample right on the surface shouldn't swamp the rest
+            AlphaMode::Falloff => options.falloff.alpha(dist).clamp(0.0, 1.0),
+            AlphaMode::Coverage { radius } if dist <= radius => 1.0,
+            AlphaMode::Coverage { .. } => continue,
+        };
+
+        sample.alpha *= weight;
+        colour += sample.into_premultiplied();
+    }
+
+    let colour = colour / offsets.len() as f32;
+
+    LinSrgba::from_premultiplied(colour)
+}
+
+/// Folds a slice of SDFs of the same type into one shape. `O` says how they
+/// are combined and `M` how sharp the seams are, colours are blended to
+/// follow the seams.
 #[derive(Clone, Copy, Debug)]
-pub struct MultiUnion<'a, T, S, M> {
+pub struct Multi<'a, T, S, M, O> {
     inner: &'a [S],
     min_func: M,
-    _pd: std::marker::PhantomData<T>,
+    _pd: std::marker::PhantomData<(T, O)>,
 }
 
-impl<'a, T, S> MultiUnion<'a, T, S, HardMin<T>> {
-    pub fn hard(sdfs: &'a [S]) -> Self {
+pub type MultiUnion<'a, T, S, M> = Multi<'a, T, S, M, Union>;
+pub type MultiIntersection<'a, T, S, M> = Multi<'a, T, S, M, Intersection>;
+pub type MultiXor<'a, T, S, M> = Multi<'a, T, S, M, Xor>;
+
+impl<'a, T, S, M, O> Multi<'a, T, S, M, O> {
+    fn new(sdfs: &'a [S], min_func: M) -> Self {
         assert!(!sdfs.is_empty());
 
-        MultiUnion {
+        Multi {
             inner: sdfs,
-            min_func: HardMin::default(),
+            min_func,
             _pd: std::marker::PhantomData,
         }
     }
+
+    /// Hollow out the result, leaving a skin `thickness` thick.
+    pub fn shell(self, thickness: T) -> Shell<T, Self> {
+        Shell::new(self, thickness)
+    }
 }
 
-fn blend_amount<T>(a: T, b: T, m: T) -> T
-where
-    T: Copy + Sub<T, Output = T> + Div<T, Output = T> + PartialOrd + One,
-{
+impl<'a, T, S, O> Multi<'a, T, S, HardMin<T>, O> {
+    pub fn hard(sdfs: &'a [S]) -> Self {
+        Self::new(sdfs, HardMin::default())
+    }
+}
+
+impl<'a, S, O> Multi<'a, f32, S, Smooth<PolySmoothMin<f32>>, O> {
+    /// Shapes closer than `radius` melt into each other like metaballs.
+    pub fn smooth(sdfs: &'a [S], radius: f32) -> Self {
+        Self::new(sdfs, Smooth::poly(radius))
+    }
+}
+
+impl<'a, S, O> Multi<'a, f32, S, Smooth<ExponentialSmoothMin<f32>>, O> {
+    /// Exponential smooth union, a larger `sharpness` gives a tighter blend.
+    pub fn exponential(sdfs: &'a [S], sharpness: f32) -> Self {
+        Self::new(
+            sdfs,
+            Smooth::new(ExponentialSmoothMin::new(sharpness), sharpness),
+        )
+    }
+}
+
+impl<'a, S, O> Multi<'a, f32, S, Smooth<PowerSmoothMin<f32>>, O> {
+    /// Power smooth union, a larger `sharpness` gives a tighter blend. This
+    /// only works on shapes which don't overlap, so only use it for unions.
+    pub fn power(sdfs: &'a [S], sharpness: f32) -> Self {
+        Self::new(sdfs, Smooth::new(PowerSmoothMin::new(sharpness), sharpness))
+    }
+}
+
+/// A `MinFunction` which also knows how much of each side ended up in the
+/// result, so colours can be blended to match the shape.
+pub trait BlendFunction<T>: MinFunction<T> {
+    /// Returns the combined distance and how far to mix from `a`'s colour
+    /// towards `b`'s.
+    fn blend(&self, a: T, b: T) -> (T, T);
+
+    /// How much further away than `a` the shape `b` can be and still change
+    /// the result.
+    fn reach(&self) -> T;
+}
+
+fn blend_amount<T: Float>(a: T, b: T, m: T) -> T {
     if a < b {
         (m - a) / (b - a)
     } else {
@@ -59,26 +481,187 @@ where
     }
 }
 
-impl<'a, T, V, S, M> SDF<T, V> for MultiUnion<'a, T, S, M>
+impl<T> BlendFunction<T> for HardMin<T>
+where
+    T: Float,
+    HardMin<T>: MinFunction<T>,
+{
+    fn blend(&self, a: T, b: T) -> (T, T) {
+        let m = self.min(a, b);
+        (m, blend_amount(a, b, m))
+    }
+
+    fn reach(&self) -> T {
+        T::zero()
+    }
+}
+
+/// One of sdfu's smooth minimums along with the parameter it was built with,
+/// which sdfu keeps to itself but the colour blending needs.
+#[derive(Clone, Copy, Debug)]
+pub struct Smooth<M> {
+    min: M,
+    k: f32,
+}
+
+impl<M> Smooth<M> {
+    fn new(min: M, k: f32) -> Self {
+        Self { min, k }
+    }
+}
+
+impl Smooth<PolySmoothMin<f32>> {
+    pub fn poly(radius: f32) -> Self {
+        Self::new(PolySmoothMin::new(radius), radius)
+    }
+}
+
+impl<M: MinFunction<f32>> MinFunction<f32> for Smooth<M> {
+    #[inline]
+    fn min(&self, a: f32, b: f32) -> f32 {
+        self.min.min(a, b)
+    }
+}
+
+impl BlendFunction<f32> for Smooth<PolySmoothMin<f32>> {
+    fn blend(&self, a: f32, b: f32) -> (f32, f32) {
+        let h = (0.5 + 0.5 * (b - a) / self.k).clamp(0.0, 1.0);
+        (self.min(a, b), 1.0 - h)
+    }
+
+    fn reach(&self) -> f32 {
+        self.k
+    }
+}
+
+impl BlendFunction<f32> for Smooth<ExponentialSmoothMin<f32>> {
+    fn blend(&self, a: f32, b: f32) -> (f32, f32) {
+        // the share of b in the sum of exponentials
+        (self.min(a, b), 1.0 / (1.0 + (self.k * (b - a)).exp()))
+    }
+
+    fn reach(&self) -> f32 {
+        f32::INFINITY
+    }
+}
+
+impl BlendFunction<f32> for Smooth<PowerSmoothMin<f32>> {
+    fn blend(&self, a: f32, b: f32) -> (f32, f32) {
+        let (pa, pb) = (
+            a.max(f32::EPSILON).powf(self.k),
+            b.max(f32::EPSILON).powf(self.k),
+        );
+        (self.min(a, b), pa / (pa + pb))
+    }
+
+    fn reach(&self) -> f32 {
+        f32::INFINITY
+    }
+}
+
+/// How `Multi` folds each shape into the ones before it.
+pub trait CsgOp {
+    /// Returns the combined distance and how far to mix from `a`'s colour
+    /// towards `b`'s.
+    fn combine<T: Float, M: BlendFunction<T>>(min_func: &M, a: T, b: T) -> (T, T);
+
+    /// Whether a shape at least `lower_bound` away leaves `dist` unchanged.
+    fn unaffected<T: Float>(_dist: T, _lower_bound: T, _reach: T) -> bool {
+        false
+    }
+
+    /// A box around the combined shape, given boxes around its parts.
+    fn bounds(_parts: impl Iterator<Item = Option<Aabb>>, _reach: f32) -> Option<Aabb> {
+        None
+    }
+}
+
+/// Inside any of the shapes.
+#[derive(Clone, Copy, Debug)]
+pub struct Union;
+
+/// Inside all of the shapes.
+#[derive(Clone, Copy, Debug)]
+pub struct Intersection;
+
+/// Inside the first shape but none of the others, the carved out surfaces
+/// take the colour of the shape which carved them.
+#[derive(Clone, Copy, Debug)]
+pub struct Subtraction;
+
+/// Inside an odd number of the shapes.
+#[derive(Clone, Copy, Debug)]
+pub struct Xor;
+
+impl CsgOp for Union {
+    #[inline]
+    fn combine<T: Float, M: BlendFunction<T>>(min_func: &M, a: T, b: T) -> (T, T) {
+        min_func.blend(a, b)
+    }
+
+    #[inline]
+    fn unaffected<T: Float>(dist: T, lower_bound: T, reach: T) -> bool {
+        lower_bound >= dist + reach
+    }
+
+    fn bounds(mut parts: impl Iterator<Item = Option<Aabb>>, reach: f32) -> Option<Aabb> {
+        let first = parts.next()??;
+
+        // smoothing can only swell the shape by less than the blend's reach
+        parts
+            .try_fold(first, |acc, part| Some(acc.union(&part?)))
+            .filter(|_| reach.is_finite())
+            .map(|bounds| bounds.expand(reach))
+    }
+}
+
+impl CsgOp for Intersection {
+    #[inline]
+    fn combine<T: Float, M: BlendFunction<T>>(min_func: &M, a: T, b: T) -> (T, T) {
+        let (dist, blend) = min_func.blend(-a, -b);
+        (-dist, blend)
+    }
+}
+
+impl CsgOp for Subtraction {
+    #[inline]
+    fn combine<T: Float, M: BlendFunction<T>>(min_func: &M, a: T, b: T) -> (T, T) {
+        let (dist, blend) = min_func.blend(-a, b);
+        (-dist, blend)
+    }
+}
+
+impl CsgOp for Xor {
+    #[inline]
+    fn combine<T: Float, M: BlendFunction<T>>(min_func: &M, a: T, b: T) -> (T, T) {
+        let (union, union_blend) = Union::combine(min_func, a, b);
+        let (inter, inter_blend) = Intersection::combine(min_func, a, b);
+        let (dist, blend) = Subtraction::combine(min_func, union, inter);
+
+        // mixing the union and intersection colours is itself a mix of a and b
+        (dist, union_blend + (inter_blend - union_blend) * blend)
+    }
+}
+
+impl<'a, T, V, S, M, O> SDF<T, V> for Multi<'a, T, S, M, O>
 where
-    T: Copy + Sub<T, Output = T> + Div<T, Output = T> + PartialOrd + One,
+    T: Float,
     f32: From<T>,
     V: sdfu::mathtypes::Vec<T>,
     S: SDF<T, V>,
-    M: MinFunction<T> + Copy,
+    M: BlendFunction<T> + Copy,
+    O: CsgOp + Copy,
 {
     #[inline]
     fn dist(&self, p: V) -> T {
         let mut sdfs = self.inner.iter();
 
-        let first = sdfs
-            .next()
-            .expect("The SDFs in the union should be nonzero");
+        let first = sdfs.next().expect("The SDFs should be nonzero");
 
         let mut dist = first.dist(p);
 
         for sdf in sdfs {
-            dist = self.min_func.min(dist, sdf.dist(p))
+            dist = O::combine(&self.min_func, dist, sdf.dist(p)).0;
         }
 
         dist
@@ -88,9 +671,7 @@ where
     fn colour(&self, p: V) -> LinSrgba {
         let mut sdfs = self.inner.iter();
 
-        let first = sdfs
-            .next()
-            .expect("The SDFs in the union should be nonzero");
+        let first = sdfs.next().expect("The SDFs should be nonzero");
 
         let mut colour = first.colour(p).into_premultiplied();
         let mut dist = first.dist(p);
@@ -98,8 +679,7 @@ where
         for sdf in sdfs {
             let b_colour = sdf.colour(p).into_premultiplied();
             let b_dist = sdf.dist(p);
-            let m = self.min_func.min(dist, b_dist);
-            let blend = blend_amount(dist, b_dist, m);
+            let (m, blend) = O::combine(&self.min_func, dist, b_dist);
 
             dist = m;
             colour = colour.mix(&b_colour, f32::from(blend));
@@ -108,3 +688,338 @@ where
         LinSrgba::from_premultiplied(colour)
     }
 }
+
+impl<'a, S, M, O> Evaluate for Multi<'a, f32, S, M, O>
+where
+    S: Evaluate,
+    M: BlendFunction<f32>,
+    O: CsgOp,
+{
+    fn eval(&self, p: Vector3<f32>) -> (f32, LinSrgba) {
+        let mut sdfs = self.inner.iter();
+
+        let first = sdfs.next().expect("The SDFs should be nonzero");
+
+        let (mut dist, colour) = first.eval(p);
+        let mut colour = colour.into_premultiplied();
+        let reach = self.min_func.reach();
+
+        for sdf in sdfs {
+            if let Some(bounds) = sdf.bounds() {
+                if O::unaffected(dist, bounds.dist(p), reach) {
+                    continue;
+                }
+            }
+
+            let (b_dist, b_colour) = sdf.eval(p);
+            let (m, blend) = O::combine(&self.min_func, dist, b_dist);
+
+            dist = m;
+            if blend > 0.0 {
+                colour = colour.mix(&b_colour.into_premultiplied(), blend);
+            }
+        }
+
+        (dist, LinSrgba::from_premultiplied(colour))
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        O::bounds(
+            self.inner.iter().map(Evaluate::bounds),
+            self.min_func.reach(),
+        )
+    }
+}
+
+/// `base` with every one of `cutters` carved out of it, the cut surfaces take
+/// the colour of the cutter which made them.
+#[derive(Clone, Copy, Debug)]
+pub struct MultiSubtraction<'a, T, B, S, M> {
+    base: B,
+    cutters: MultiUnion<'a, T, S, M>,
+}
+
+impl<'a, T, B, S, M> MultiSubtraction<'a, T, B, S, M> {
+    /// Hollow out the result, leaving a skin `thickness` thick.
+    pub fn shell(self, thickness: T) -> Shell<T, Self> {
+        Shell::new(self, thickness)
+    }
+}
+
+impl<'a, T, B, S> MultiSubtraction<'a, T, B, S, HardMin<T>> {
+    pub fn hard(base: B, cutters: &'a [S]) -> Self {
+        Self {
+            base,
+            cutters: MultiUnion::hard(cutters),
+        }
+    }
+}
+
+impl<'a, B, S> MultiSubtraction<'a, f32, B, S, Smooth<PolySmoothMin<f32>>> {
+    /// Cuts with rounded edges of `radius`.
+    pub fn smooth(base: B, cutters: &'a [S], radius: f32) -> Self {
+        Self {
+            base,
+            cutters: MultiUnion::smooth(cutters, radius),
+        }
+    }
+}
+
+impl<'a, T, V, B, S, M> SDF<T, V> for MultiSubtraction<'a, T, B, S, M>
+where
+    T: Float,
+    f32: From<T>,
+    V: sdfu::mathtypes::Vec<T>,
+    B: SDF<T, V>,
+    S: SDF<T, V>,
+    M: BlendFunction<T> + Copy,
+{
+    #[inline]
+    fn dist(&self, p: V) -> T {
+        Subtraction::combine(
+            &self.cutters.min_func,
+            self.base.dist(p),
+            self.cutters.dist(p),
+        )
+        .0
+    }
+
+    #[inline]
+    fn colour(&self, p: V) -> LinSrgba {
+        let (_, blend) = Subtraction::combine(
+            &self.cutters.min_func,
+            self.base.dist(p),
+            self.cutters.dist(p),
+        );
+
+        let colour = self.base.colour(p).into_premultiplied().mix(
+            &self.cutters.colour(p).into_premultiplied(),
+            f32::from(blend),
+        );
+
+        LinSrgba::from_premultiplied(colour)
+    }
+}
+
+impl<'a, B, S, M> Evaluate for MultiSubtraction<'a, f32, B, S, M>
+where
+    B: Evaluate,
+    S: Evaluate,
+    M: BlendFunction<f32>,
+{
+    fn eval(&self, p: Vector3<f32>) -> (f32, LinSrgba) {
+        let (base_dist, base_colour) = self.base.eval(p);
+        let (cut_dist, cut_colour) = self.cutters.eval(p);
+        let (dist, blend) = Subtraction::combine(&self.cutters.min_func, base_dist, cut_dist);
+
+        let colour = base_colour
+            .into_premultiplied()
+            .mix(&cut_colour.into_premultiplied(), blend);
+
+        (dist, LinSrgba::from_premultiplied(colour))
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        // cutting only ever removes from the base
+        self.base.bounds()
+    }
+}
+
+/// Just the surface of `inner`, `thickness` thick.
+#[derive(Clone, Copy, Debug)]
+pub struct Shell<T, S> {
+    inner: S,
+    thickness: T,
+}
+
+impl<T, S> Shell<T, S> {
+    pub fn new(sdf: S, thickness: T) -> Self {
+        Self {
+            inner: sdf,
+            thickness,
+        }
+    }
+}
+
+impl<T, V, S> SDF<T, V> for Shell<T, S>
+where
+    T: Float,
+    V: sdfu::mathtypes::Vec<T>,
+    S: SDF<T, V>,
+{
+    #[inline]
+    fn dist(&self, p: V) -> T {
+        self.inner.dist(p).abs() - self.thickness
+    }
+
+    #[inline]
+    fn colour(&self, p: V) -> LinSrgba {
+        self.inner.colour(p)
+    }
+}
+
+impl<S: Evaluate> Evaluate for Shell<f32, S> {
+    #[inline]
+    fn eval(&self, p: Vector3<f32>) -> (f32, LinSrgba) {
+        let (dist, colour) = self.inner.eval(p);
+        (dist.abs() - self.thickness, colour)
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        self.inner
+            .bounds()
+            .map(|bounds| bounds.expand(self.thickness))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::vector;
+
+    use super::*;
+
+    /// Two spheres of radius two overlapping between `x = 0` and `x = 2`.
+    fn pair() -> [Bounded<Translate<Vector3<f32>, Sphere<f32>>>; 2] {
+        [
+            Bounded::sphere(
+                vector![0.0, 0.0, 0.0],
+                2.0,
+                LinSrgba::new(1.0, 0.0, 0.0, 1.0),
+            ),
+            Bounded::sphere(
+                vector![2.0, 0.0, 0.0],
+                2.0,
+                LinSrgba::new(0.0, 0.0, 1.0, 1.0),
+            ),
+        ]
+    }
+
+    fn inside<S: Evaluate>(sdf: &S, p: Vector3<f32>) -> bool {
+        sdf.eval(p).0 < 0.0
+    }
+
+    // one point in only the first sphere, one in both, one in only the second
+    const FIRST: Vector3<f32> = Vector3::new(-1.5, 0.0, 0.0);
+    const BOTH: Vector3<f32> = Vector3::new(1.0, 0.0, 0.0);
+    const SECOND: Vector3<f32> = Vector3::new(3.5, 0.0, 0.0);
+
+    #[test]
+    fn zero_width_falloffs_are_hard() {
+        for falloff in [
+            Falloff::Smoothstep {
+                start: 0.5,
+                end: 0.5,
+            },
+            Falloff::Gaussian { sigma: 0.0 },
+        ] {
+            assert_eq!(falloff.alpha(-1.0), 1.0, "{:?}", falloff);
+            assert_eq!(falloff.alpha(0.0), 1.0, "{:?}", falloff);
+            assert_eq!(falloff.alpha(1.0), 0.0, "{:?}", falloff);
+        }
+    }
+
+    #[test]
+    fn multi_ops() {
+        let spheres = pair();
+
+        let union = MultiUnion::hard(&spheres);
+        assert!([FIRST, BOTH, SECOND].iter().all(|&p| inside(&union, p)));
+
+        let intersection = MultiIntersection::hard(&spheres);
+        assert!(!inside(&intersection, FIRST));
+        assert!(inside(&intersection, BOTH));
+        assert!(!inside(&intersection, SECOND));
+
+        let xor = MultiXor::hard(&spheres);
+        assert!(inside(&xor, FIRST));
+        assert!(!inside(&xor, BOTH));
+        assert!(inside(&xor, SECOND));
+    }
+
+    #[test]
+    fn subtraction_takes_the_cutters_colour() {
+        let [base, cutter] = pair();
+        let cut = MultiSubtraction::hard(base, std::slice::from_ref(&cutter));
+
+        assert!(inside(&cut, FIRST));
+        assert!(!inside(&cut, BOTH));
+        assert!(!inside(&cut, SECOND));
+
+        // on the carved face, which is the cutter's surface
+        let (dist, colour) = cut.eval(vector![0.0, 0.0, 0.0]);
+        assert_eq!(dist, 0.0);
+        assert_eq!(colour, LinSrgba::new(0.0, 0.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn shells_are_hollow() {
+        let spheres = pair();
+
+        let shell = MultiUnion::hard(&spheres).shell(0.25);
+        assert!(!inside(&shell, vector![-0.5, 0.0, 0.0]));
+        assert!(inside(&shell, vector![-2.1, 0.0, 0.0]));
+        assert_eq!(
+            shell.bounds(),
+            MultiUnion::hard(&spheres).bounds().map(|b| b.expand(0.25))
+        );
+
+        let [base, cutter] = spheres;
+        let shell = MultiSubtraction::smooth(base, std::slice::from_ref(&cutter), 0.5).shell(0.25);
+        assert!(!inside(&shell, FIRST));
+        assert!(inside(&shell, vector![-2.0, 0.0, 0.0]));
+    }
+
+    #[test]
+    fn culling_does_not_change_the_frame() {
+        // a clump in one corner, so whole voxels are culled, then with more
+        // spread through the cube, so spheres are skipped within voxels
+        let clump = (0..12).map(|i| vector![(i % 3) as f32 * 0.7, (i / 3) as f32 * 0.4, 1.0]);
+        let spread = (0..12).map(|i| {
+            let i = i as f32;
+            vector![(i * 2.39).sin() * 3.5 + 3.5, (i * 0.37) % 7.0, 6.0]
+        });
+        let colour = |i: usize| LinSrgba::new((i % 4) as f32 / 3.0, 0.5, 0.2, 1.0);
+
+        for centres in [
+            clump.clone().collect::<Vec<_>>(),
+            clump.chain(spread).collect(),
+        ] {
+            let plain = centres
+                .iter()
+                .enumerate()
+                .map(|(i, &c)| Sphere::new(0.6, colour(i)).translate(c))
+                .collect::<Vec<_>>();
+            let bounded = centres
+                .iter()
+                .enumerate()
+                .map(|(i, &c)| Bounded::sphere(c, 0.6, colour(i)))
+                .collect::<Vec<_>>();
+
+            let options = [
+                RenderOptions::default(),
+                RenderOptions::antialiased(),
+                RenderOptions::default().with_falloff(Falloff::Hard { threshold: 0.5 }),
+                RenderOptions::default().with_alpha(AlphaMode::Coverage { radius: 0.5 }),
+            ];
+
+            for options in options {
+                let (mut unculled, mut culled) = (Frame::new(), Frame::new());
+                render_sdf_with(MultiUnion::smooth(&plain, 0.8), &mut unculled, &options);
+                render_sdf_with(MultiUnion::smooth(&bounded, 0.8), &mut culled, &options);
+
+                // culled voxels are only ones which would round to nothing
+                // when sent out, whose colour doesn't matter
+                for ((x, y, z, a), (_, _, _, b)) in unculled.pixels().zip(culled.pixels()) {
+                    assert!(
+                        a == b || (a.alpha < INVISIBLE && b.alpha < INVISIBLE),
+                        "{:?} at {:?}: {:?} vs {:?}",
+                        options,
+                        (x, y, z),
+                        a,
+                        b
+                    );
+                }
+            }
+        }
+    }
+}
@@ -24,24 +24,31 @@ struct State {
 }
 
 #[derive(Resource)]
-struct Lol<T, const NAME: &'static str>(Arc<RwLock<T>>);
+struct Lol<T> {
+    inner: Arc<RwLock<T>>,
+    name: &'static str,
+}
 
-impl<T: Inspectable + Send + Sync + 'static, const NAME: &'static str> Plugin for Lol<T, NAME> {
+impl<T: Inspectable + Send + Sync + 'static> Plugin for Lol<T> {
     fn build(&self, app: &mut App) {
-        app.insert_resource(Lol::<T, NAME>(self.0.clone()))
-            .add_plugin(InspectorPlugin::<Lol<T, NAME>>::new_insert_manually());
-
-        app.add_startup_system(|mut windows: ResMut<InspectorWindows>| {
-            windows.window_data_mut::<Lol<T, NAME>>().name = NAME.to_owned();
+        app.insert_resource(Lol::<T> {
+            inner: self.inner.clone(),
+            name: self.name,
+        })
+        .add_plugin(InspectorPlugin::<Lol<T>>::new_insert_manually());
+
+        let name = self.name;
+        app.add_startup_system(move |mut windows: ResMut<InspectorWindows>| {
+            windows.window_data_mut::<Lol<T>>().name = name.to_owned();
         });
     }
 }
 
-impl<T: Inspectable, const NAME: &'static str> Inspectable for Lol<T, NAME> {
+impl<T: Inspectable> Inspectable for Lol<T> {
     type Attributes = T::Attributes;
 
     fn ui_raw(&mut self, ui: &mut bevy_inspector_egui::egui::Ui, options: Self::Attributes) {
-        self.0.write().unwrap().ui_raw(ui, options);
+        self.inner.write().unwrap().ui_raw(ui, options);
     }
 
     fn setup(app: &mut App) {
@@ -54,7 +61,7 @@ impl<T: Inspectable, const NAME: &'static str> Inspectable for Lol<T, NAME> {
         options: Self::Attributes,
         context: &mut bevy_inspector_egui::Context,
     ) -> bool {
-        self.0.write().unwrap().ui(ui, options, context)
+        self.inner.write().unwrap().ui(ui, options, context)
     }
 }
 
@@ -117,7 +124,9 @@ fn update_driver_system(
     lights: Query<&Coordinate>,
     mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
-    state.driver.step();
+    if let Err(e) = state.driver.step() {
+        error!("backend failed: {}", e);
+    }
 
     for coord in lights.iter() {
         let value = state
@@ -148,7 +157,10 @@ pub fn main() {
         .insert_resource(State { driver })
         .add_plugins(DefaultPlugins)
         .add_plugin(bevy_flycam::NoCameraPlayerPlugin)
-        .add_plugin(Lol::<_, "Animation">(animation.clone()))
+        .add_plugin(Lol {
+            inner: animation.clone(),
+            name: "Animation",
+        })
         .add_startup_system(setup)
         .add_system(update_driver_system)
         .run();
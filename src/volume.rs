@@ -0,0 +1,150 @@
+use nalgebra::Vector3;
+use palette::{Blend, LinSrgba};
+
+use crate::frame::Frame;
+
+/// A grid of voxels of any size, which is what the importers produce before
+/// it gets squeezed into the cube. `y` is up, as in `Frame`.
+#[derive(Clone, Debug)]
+pub struct Volume {
+    size: [usize; 3],
+    voxels: Vec<LinSrgba>,
+}
+
+impl Volume {
+    pub fn new(size: [usize; 3]) -> Self {
+        let len = size
+            .iter()
+            .try_fold(1usize, |len, &n| len.checked_mul(n))
+            .expect("volume too big");
+
+        Self {
+            size,
+            voxels: vec![LinSrgba::new(0.0, 0.0, 0.0, 0.0); len],
+        }
+    }
+
+    pub fn size(&self) -> [usize; 3] {
+        self.size
+    }
+
+    fn index(&self, x: usize, y: usize, z: usize) -> usize {
+        x + self.size[0] * (y + self.size[1] * z)
+    }
+
+    pub fn get(&self, x: usize, y: usize, z: usize) -> LinSrgba {
+        self.voxels[self.index(x, y, z)]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, z: usize, val: LinSrgba) {
+        let idx = self.index(x, y, z);
+        self.voxels[idx] = val;
+    }
+
+    /// Scale the volume to fit the cube, keeping its proportions and centring
+    /// it. Shrinking averages the voxels that land in each cell, so thin
+    /// parts fade rather than vanish, growing picks the nearest voxel.
+    pub fn fit(&self) -> Frame {
+        let largest = self.size.iter().copied().max().unwrap_or(0);
+
+        self.resample([largest as f32 / Frame::LAYERS as f32; 3])
+    }
+
+    /// Like `fit`, but each axis is scaled separately to fill the cube.
+    pub fn stretch(&self) -> Frame {
+        self.resample(self.size.map(|n| n as f32 / Frame::LAYERS as f32))
+    }
+
+    /// The middle of the volume at one voxel per voxel, anything past the
+    /// edges of the cube is cut off.
+    pub fn crop(&self) -> Frame {
+        self.resample([1.0; 3])
+    }
+
+    /// `scale` is how many of our voxels go along each axis of a cube voxel.
+    fn resample(&self, scale: [f32; 3]) -> Frame {
+        let mut frame = Frame::new();
+
+        if self.voxels.is_empty() {
+            return frame;
+        }
+
+        let span = |axis: usize, cell: u8| {
+            let scale = scale[axis];
+            let offset = (self.size[axis] as f32 - scale * Frame::LAYERS as f32) / 2.0;
+            let lo = cell as f32 * scale + offset;
+            let hi = lo + scale;
+
+            let (start, end) = if scale < 1.0 {
+                let mid = ((lo + hi) / 2.0).floor();
+                (mid, mid + 1.0)
+            } else {
+                (lo.floor(), hi.ceil())
+            };
+
+            let clamp = |v: f32| v.clamp(0.0, self.size[axis] as f32) as usize;
+            clamp(start)..clamp(end)
+        };
+
+        for (x, y, z, pix) in frame.pixels_mut() {
+            let (xs, ys, zs) = (span(0, x), span(1, y), span(2, z));
+            let count = xs.len() * ys.len() * zs.len();
+
+            if count == 0 {
+                continue;
+            }
+
+            let mut colour = LinSrgba::new(0.0, 0.0, 0.0, 0.0).into_premultiplied();
+
+            for sz in zs {
+                for sy in ys.clone() {
+                    for sx in xs.clone() {
+                        colour += self.get(sx, sy, sz).into_premultiplied();
+                    }
+                }
+            }
+
+            *pix = LinSrgba::from_premultiplied(colour / count as f32);
+        }
+
+        frame
+    }
+}
+
+/// The colour of `frame` at a point between voxel centres, blending the eight
+/// around it. Outside the cube is transparent.
+pub fn sample(frame: &Frame, p: Vector3<f32>) -> LinSrgba {
+    let base = p.map(f32::floor);
+    let t = p - base;
+
+    let mut colour = LinSrgba::new(0.0, 0.0, 0.0, 0.0).into_premultiplied();
+
+    for corner in 0..8 {
+        let offset = Vector3::new(corner & 1, (corner >> 1) & 1, (corner >> 2) & 1);
+        let pos = base + offset.map(|o| o as f32);
+
+        if pos
+            .iter()
+            .any(|&c| !(0.0..Frame::LAYERS as f32).contains(&c))
+        {
+            continue;
+        }
+
+        let weight = (0..3)
+            .map(|axis| {
+                if offset[axis] == 1 {
+                    t[axis]
+                } else {
+                    1.0 - t[axis]
+                }
+            })
+            .product::<f32>();
+
+        if weight > 0.0 {
+            let pix = frame.get(pos.x as usize, pos.y as usize, pos.z as usize);
+            colour += pix.into_premultiplied() * weight;
+        }
+    }
+
+    LinSrgba::from_premultiplied(colour)
+}